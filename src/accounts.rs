@@ -0,0 +1,130 @@
+// Player accounts for gin-server --accounts <file>. Passwords are kept as argon2 hashes, one
+// account a line as "name<TAB>hash", and logging in gives a token that stands for the account
+// on later connections until it expires or the server restarts.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::{thread_rng, Rng};
+
+use crate::protocol::valid_name;
+use crate::server::random_code;
+
+const MIN_PASSWORD: usize = 8;
+const TOKEN_LENGTH: usize = 24;
+// how long a login token lasts
+const TOKEN_LIFETIME: Duration = Duration::from_secs(30 * 24 * 3600);
+
+pub struct Accounts {
+    path: String,
+    // each account's name and password hash
+    accounts: Vec<(String, String)>,
+    tokens: Vec<LoginToken>,
+}
+
+struct LoginToken {
+    token: String,
+    name: String,
+    issued: Instant,
+}
+
+impl Accounts {
+    // the accounts saved in the file, if there is one yet
+    pub fn load(path: &str) -> Self {
+        let mut accounts = Vec::new();
+        if let Ok(text) = fs::read_to_string(path) {
+            for line in text.lines().filter(|line| !line.is_empty()) {
+                match line.split_once('\t') {
+                    Some((name, hash)) => accounts.push((String::from(name), String::from(hash))),
+                    None => println!("skipping an invalid account in {}", path),
+                }
+            }
+        }
+        Accounts {
+            path: String::from(path),
+            accounts,
+            tokens: Vec::new(),
+        }
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.accounts.iter().any(|(account, _)| account == name)
+    }
+
+    // the password hash to check a login against
+    pub fn hash(&self, name: &str) -> Option<String> {
+        self.accounts
+            .iter()
+            .find(|(account, _)| account == name)
+            .map(|(_, hash)| hash.clone())
+    }
+
+    // adds an account whose password has already been hashed, and saves it
+    pub fn add(&mut self, name: &str, hash: String) -> Result<(), String> {
+        // a newline in the name would write a second account to the file
+        if !valid_name(name) {
+            return Err(String::from("choose another name"));
+        }
+        if self.exists(name) {
+            return Err(format!("{} is already taken", name));
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("could not save the account: {}", e))?;
+        writeln!(file, "{}\t{}", name, hash)
+            .map_err(|e| format!("could not save the account: {}", e))?;
+        self.accounts.push((String::from(name), hash));
+        Ok(())
+    }
+
+    pub fn issue_token(&mut self, name: &str) -> String {
+        let token = random_code(TOKEN_LENGTH);
+        self.tokens.push(LoginToken {
+            token: token.clone(),
+            name: String::from(name),
+            issued: Instant::now(),
+        });
+        token
+    }
+
+    // the account a token was issued for, while it lasts
+    pub fn authenticate(&mut self, token: &str) -> Option<String> {
+        self.tokens
+            .retain(|login| login.issued.elapsed() < TOKEN_LIFETIME);
+        self.tokens
+            .iter()
+            .find(|login| login.token == token)
+            .map(|login| login.name.clone())
+    }
+}
+
+// hashing is slow on purpose, so these are called without the accounts locked
+
+pub fn hash_password(password: &str) -> Result<String, String> {
+    if password.chars().count() < MIN_PASSWORD {
+        return Err(format!(
+            "passwords need at least {} characters",
+            MIN_PASSWORD
+        ));
+    }
+    let salt: [u8; 16] = thread_rng().gen();
+    let salt = SaltString::encode_b64(&salt).map_err(|e| e.to_string())?;
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,344 @@
+// gin-server --api <address>: match history, hand replays, player stats and ratings as JSON
+// over HTTP, for dashboards. Every request reads the saved hands afresh, so a hand shows up as
+// soon as it is saved. The endpoints are described in docs/api.md.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use serde_json::{json, Value};
+
+use crate::export::{hand_rows, ExportFilter, HandRow};
+use crate::rating::Ladder;
+use crate::record::{action_to_log, format_date, HandRecord};
+use crate::stats::{match_results, MatchResult, PlayerProfile};
+use crate::{notation, Card, Player};
+
+// serves the hands saved in the directory
+pub fn serve(listener: TcpListener, hands: String) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let hands = hands.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle(stream, &hands) {
+                        println!("API request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => println!("connection failed: {}", e),
+        }
+    }
+}
+
+fn handle(mut stream: TcpStream, hands: &str) -> Result<(), String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut request = String::new();
+    reader.read_line(&mut request).map_err(|e| e.to_string())?;
+    // the headers say nothing a GET needs
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    let (status, body) = match request.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["GET", target, _] => respond(target, &HandRecord::load_dir(hands)),
+        [_, _, _] => (405, json!({"error": "only GET is supported"})),
+        _ => (400, json!({"error": "invalid request"})),
+    };
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+    .map_err(|e| e.to_string())
+}
+
+// the status and body for a request target such as "/players/Alice?rules=Quick"
+fn respond(target: &str, records: &[HandRecord]) -> (u16, Value) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode)
+        .collect();
+    let parameter = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| decode(value))
+    };
+    let segments: Vec<&str> = segments.iter().map(|segment| segment.as_str()).collect();
+    let found = match segments.as_slice() {
+        ["matches"] => Some(matches(records, parameter("player"), parameter("rules"))),
+        ["matches", id] => id.parse().ok().and_then(|id| match_hands(records, id)),
+        ["matches", id, "hands", hand] => match (id.parse(), hand.parse()) {
+            (Ok(id), Ok(hand)) => replay(records, id, hand),
+            _ => None,
+        },
+        ["players", name] => player(records, name),
+        ["players", name, "ratings"] => {
+            let rules = parameter("rules").unwrap_or(String::from("Standard"));
+            ratings(records, name, &rules)
+        }
+        ["leaderboard"] => {
+            let rules = parameter("rules").unwrap_or(String::from("Standard"));
+            Some(leaderboard(records, &rules))
+        }
+        _ => None,
+    };
+    match found {
+        Some(body) => (200, body),
+        None => (404, json!({"error": format!("nothing at {}", path)})),
+    }
+}
+
+// the decided matches, most recent last
+fn matches(records: &[HandRecord], player: Option<String>, rules: Option<String>) -> Value {
+    let results: Vec<Value> = match_results(records)
+        .iter()
+        .filter(|result| result.winner.is_some())
+        .filter(|result| {
+            player.as_ref().is_none_or(|player| {
+                result.first_player == *player || result.second_player == *player
+            })
+        })
+        .filter(|result| rules.as_ref().is_none_or(|rules| result.rules == *rules))
+        .map(|result| match_json(result, records))
+        .collect();
+    json!(results)
+}
+
+fn match_json(result: &MatchResult, records: &[HandRecord]) -> Value {
+    json!({
+        "match": result.match_id,
+        "rules": result.rules,
+        "first_player": result.first_player,
+        "second_player": result.second_player,
+        "winner": result.winner,
+        "played": result.played,
+        "date": format_date(result.played),
+        "hands": match_records(records, result.match_id).len(),
+    })
+}
+
+// a match and each of its finished hands
+fn match_hands(records: &[HandRecord], id: u64) -> Option<Value> {
+    let results = match_results(records);
+    let result = results.iter().find(|result| result.match_id == id)?;
+    let mut body = match_json(result, records);
+    let hands: Vec<Value> = hand_rows(records, &ExportFilter::new())
+        .iter()
+        .filter(|row| row.match_id == id)
+        .map(hand_json)
+        .collect();
+    body["hands"] = json!(hands);
+    Some(body)
+}
+
+fn hand_json(row: &HandRow) -> Value {
+    json!({
+        "hand": row.hand,
+        "date": row.date,
+        // seeds are sent as text since most do not fit in a JSON number exactly
+        "seed": row.seed.to_string(),
+        "knocker": row.knocker,
+        "outcome": row.outcome,
+        "winner": row.winner,
+        "points": row.points,
+        "knocker_deadwood": row.knocker_deadwood,
+        "defender_deadwood": row.defender_deadwood,
+        "turns": row.turns,
+    })
+}
+
+// the hands of a match in the order they were played, as hand_rows numbers them
+fn match_records(records: &[HandRecord], id: u64) -> Vec<&HandRecord> {
+    records
+        .iter()
+        .filter(|record| record.match_id == id)
+        .collect()
+}
+
+// the hand dealt again and played through one move at a time
+fn replay(records: &[HandRecord], id: u64, hand: usize) -> Option<Value> {
+    let record = *match_records(records, id).get(hand.checked_sub(1)?)?;
+    let mut steps = Vec::new();
+    for step in 0..=record.moves.len() {
+        let game = record.play_to(step).ok()?;
+        let played = step.checked_sub(1).map(|index| {
+            let player_move = &record.moves[index];
+            json!({"player": player_move.player, "action": action_to_log(&player_move.action)})
+        });
+        steps.push(json!({
+            "move": played,
+            "turn": game.current_turn,
+            "players": [player_json(&game.first_player), player_json(&game.second_player)],
+            "discard_pile": codes(&game.discard_pile.cards),
+            "stock": game.deck.cards.len(),
+        }));
+    }
+    Some(json!({
+        "match": record.match_id,
+        "hand": hand,
+        "rules": record.rules,
+        "first_player": record.first_player,
+        "second_player": record.second_player,
+        "first_turn": record.first_turn,
+        "deck": codes(&record.deck),
+        "winner": record.winner,
+        "points": record.points,
+        "notation": notation::write(record),
+        "chat": record
+            .chat
+            .iter()
+            .map(|line| json!({
+                "after": line.after,
+                "player": line.player,
+                "text": line.text,
+                "emote": line.emote,
+            }))
+            .collect::<Vec<_>>(),
+        "steps": steps,
+    }))
+}
+
+fn player_json(player: &Player) -> Value {
+    json!({
+        "name": player.name,
+        "hand": codes(&player.hand),
+        "melds": player
+            .melds
+            .collection
+            .iter()
+            .map(|meld| codes(meld))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn codes(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|card| card.code()).collect()
+}
+
+// the player's stats and their rating under each rule set
+fn player(records: &[HandRecord], name: &str) -> Option<Value> {
+    let profile = PlayerProfile::from_records(name, records);
+    if profile.hands_played == 0 && profile.matches_played == 0 {
+        return None;
+    }
+    let ladder = Ladder::from_results(&match_results(records));
+    let ratings: Vec<Value> = ladder
+        .players
+        .iter()
+        .filter(|player| player.name == name)
+        .map(|player| {
+            json!({
+                "rules": player.rules,
+                "rating": player.rating.rating,
+                "deviation": player.rating.deviation,
+                "matches": player.matches,
+            })
+        })
+        .collect();
+    Some(json!({
+        "name": profile.name,
+        "matches_played": profile.matches_played,
+        "matches_won": profile.matches_won,
+        "hands_played": profile.hands_played,
+        "hands_won": profile.hands_won,
+        "gins": profile.gins,
+        "knocks": profile.knocks,
+        "undercuts_given": profile.undercuts_given,
+        "undercuts_received": profile.undercuts_received,
+        "points": profile.points,
+        "gin_rate": profile.gin_rate(),
+        "knock_rate": profile.knock_rate(),
+        "undercut_given_rate": profile.undercut_given_rate(),
+        "undercut_received_rate": profile.undercut_received_rate(),
+        "average_knock_deadwood": profile.average_knock_deadwood(),
+        "average_turns": profile.average_turns(),
+        "points_per_hand": profile.points_per_hand(),
+        "ratings": ratings,
+    }))
+}
+
+// the player's rating after each of their matches under the rules
+fn ratings(records: &[HandRecord], name: &str, rules: &str) -> Option<Value> {
+    let ladder = Ladder::from_results(&match_results(records));
+    let player = ladder.player(name, rules)?;
+    let history: Vec<Value> = player
+        .history
+        .iter()
+        .map(|(played, rating, deviation)| {
+            json!({
+                "played": played,
+                "date": format_date(*played),
+                "rating": rating,
+                "deviation": deviation,
+            })
+        })
+        .collect();
+    Some(json!({"name": name, "rules": rules, "history": history}))
+}
+
+fn leaderboard(records: &[HandRecord], rules: &str) -> Value {
+    let ladder = Ladder::from_results(&match_results(records));
+    let players: Vec<Value> = ladder
+        .leaderboard(rules)
+        .iter()
+        .enumerate()
+        .map(|(index, player)| {
+            json!({
+                "rank": index + 1,
+                "name": player.name,
+                "rating": player.rating.rating,
+                "deviation": player.rating.deviation,
+                "matches": player.matches,
+            })
+        })
+        .collect();
+    json!({"rules": rules, "players": players})
+}
+
+// undoes percent encoding, and + for a space in a query
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes[index] {
+            b'%' => text
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match (escaped, bytes[index]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (None, b'+') => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,123 @@
+use std::env;
+use std::time::Duration;
+
+use gin::clock::Timeout;
+use gin::server::{self, ServerOptions};
+
+const USAGE: &str = "usage: gin-server [address] [--websocket <address>] [--api <address>] \
+     [--reconnect <seconds>] [--bot] [--reveal <seconds>] [--games <dir>] [--webhook <url>] \
+     [--move-time <seconds>] [--bank <seconds>] [--increment <seconds>] \
+     [--on-timeout <play|forfeit>] [--accounts <file>]";
+
+// gin-server [address] [--websocket <address>] [--api <address>] [--reconnect <seconds>] [--bot]
+// [--reveal <seconds>] [--games <dir>] [--webhook <url>] [--move-time <seconds>]
+// [--bank <seconds>] [--increment <seconds>] [--on-timeout <play|forfeit>] [--accounts <file>]
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut address = String::from("127.0.0.1:7878");
+    let mut websocket_address = None;
+    let mut api_address = None;
+    let mut options = ServerOptions::standard();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--websocket" => match args.next() {
+                Some(value) => websocket_address = Some(value.clone()),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // match history, stats and ratings as JSON over HTTP
+            "--api" => match args.next() {
+                Some(value) => api_address = Some(value.clone()),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // how long a disconnected player's seat is held for them
+            "--reconnect" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(seconds) => options.reconnection.timeout = Duration::from_secs(seconds),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // a bot takes over a seat that is not taken back in time
+            "--bot" => options.reconnection.bot = true,
+            // spectators see both hands this long after each move
+            "--reveal" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(seconds) => options.reveal = Some(Duration::from_secs(seconds)),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // where correspondence games are kept
+            "--games" => match args.next() {
+                Some(dir) => options.games = dir.clone(),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // where players are told it is their move in a correspondence game
+            "--webhook" => match args.next() {
+                Some(url) => options.webhook = Some(url.clone()),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // the longest a turn can take
+            "--move-time" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(seconds) => options.time_control.per_move = Some(Duration::from_secs(seconds)),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // each player's time for the whole match
+            "--bank" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(seconds) => options.time_control.bank = Some(Duration::from_secs(seconds)),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // added back to a player's bank after each of their turns
+            "--increment" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(seconds) => options.time_control.increment = Duration::from_secs(seconds),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // what becomes of a player who runs out of time
+            "--on-timeout" => match args.next().map(|value| value.as_str()) {
+                Some("play") => options.time_control.timeout = Timeout::Play,
+                Some("forfeit") => options.time_control.timeout = Timeout::Forfeit,
+                _ => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            // players log in to accounts kept in the file rather than choosing any name
+            "--accounts" => match args.next() {
+                Some(path) => options.accounts = Some(path.clone()),
+                None => {
+                    println!("{}", USAGE);
+                    return;
+                }
+            },
+            _ => address = arg.clone(),
+        }
+    }
+    server::run(
+        &address,
+        websocket_address.as_deref(),
+        api_address.as_deref(),
+        options,
+    );
+}
@@ -0,0 +1,134 @@
+// A simple player for a seat nobody is playing. It only looks at what its player could see: it
+// takes the top of the discard pile when that leaves it less deadwood, goes down as soon as it
+// can, and otherwise throws whichever card leaves the least deadwood.
+
+use std::time::Duration;
+
+use crate::{meld, Action, Card, GinGame, Phase, RuleSet};
+
+pub const BOT: &str = "Bot";
+// so the player can follow the bot's moves
+pub const BOT_PAUSE: Duration = Duration::from_millis(600);
+
+impl GinGame {
+    // a move for the player whose turn it is
+    pub fn bot_action(&self) -> Action {
+        let hand = &self.current_player().hand;
+        if self.phase == Phase::Draw {
+            if let Some(top) = self.discard_pile.cards.last() {
+                let mut with_top = hand.clone();
+                with_top.push(top.clone());
+                // the top card cannot be thrown straight back
+                let throwable: Vec<usize> = (0..hand.len()).collect();
+                let (_, deadwood) = least_deadwood(&with_top, &throwable);
+                if deadwood < meld::deadwood(&meld::best_melds(hand).1) {
+                    return Action::DrawDiscard;
+                }
+            }
+            return Action::DrawStock;
+        }
+
+        let (index, deadwood) = least_deadwood(hand, &self.discard_indexes());
+        let going_down = self.knock_status || self.gin_status;
+        if !going_down && deadwood == 0 {
+            Action::Gin
+        } else if !going_down && deadwood <= self.rules.knock_limit {
+            Action::Knock
+        } else {
+            Action::Discard(hand[index].clone())
+        }
+    }
+}
+
+// the card to throw, of those at the indexes given, that leaves the least deadwood, and how
+// much it leaves
+fn least_deadwood(hand: &[Card], throwable: &[usize]) -> (usize, i32) {
+    throwable
+        .iter()
+        .map(|&index| {
+            let mut rest = hand.to_vec();
+            rest.remove(index);
+            (index, meld::deadwood(&meld::best_melds(&rest).1))
+        })
+        .min_by_key(|(_, deadwood)| *deadwood)
+        .unwrap()
+}
+
+// a match of one player against the bot, as the tui and the web page play it
+pub struct BotMatch {
+    pub game: GinGame,
+    pub player: String,
+    // the player's and the bot's
+    pub totals: [i32; 2],
+    // whether the hand has been scored and added to the totals
+    pub scored: bool,
+}
+
+impl BotMatch {
+    // a match under the given rules, with the first hand dealt
+    pub(crate) fn new(player: &str, rules: &RuleSet) -> Result<Self, String> {
+        if player.is_empty() || player == BOT {
+            return Err(format!("choose a name other than {}", BOT));
+        }
+        Ok(BotMatch::with_game(player, deal(player, rules)))
+    }
+
+    // a match picked up at a hand already under way
+    pub fn with_game(player: &str, game: GinGame) -> Self {
+        BotMatch {
+            game,
+            player: String::from(player),
+            totals: [0, 0],
+            scored: false,
+        }
+    }
+
+    pub fn bot_to_play(&self) -> bool {
+        let playing = self.game.phase == Phase::Draw || self.game.phase == Phase::Discard;
+        playing && self.game.current_turn != self.player
+    }
+
+    // lays the hands down once someone has gone down, then adds the hand to the totals
+    pub fn finish_hand(&mut self) {
+        if self.game.phase == Phase::Showdown {
+            self.game.showdown();
+        }
+        if self.game.phase != Phase::Over || self.scored {
+            return;
+        }
+        let winner = &self.game.score.player;
+        if *winner == self.player {
+            self.totals[0] += self.game.score.points;
+        } else if !winner.is_empty() {
+            self.totals[1] += self.game.score.points;
+        }
+        self.scored = true;
+    }
+
+    pub fn match_over(&self) -> bool {
+        self.totals
+            .iter()
+            .any(|total| *total >= self.game.rules.target_score)
+    }
+
+    // deals the next hand once this one is over, unless the match is
+    pub fn next_hand(&mut self) -> Result<(), String> {
+        if !self.scored {
+            return Err(String::from("the hand is not over"));
+        }
+        if self.match_over() {
+            return Err(String::from("the match is over"));
+        }
+        self.game = deal(&self.player, &self.game.rules);
+        self.scored = false;
+        Ok(())
+    }
+}
+
+fn deal(player: &str, rules: &RuleSet) -> GinGame {
+    let mut game = GinGame::new(String::from(player), String::from(BOT));
+    game.rules = rules.clone();
+    game.decide_first_turn();
+    game.deal_starting_hands();
+    game
+}
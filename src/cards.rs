@@ -0,0 +1,79 @@
+// How cards are written in the terminal: the rank and a suit symbol, hearts and diamonds in red,
+// e.g. Q♥ or 10♣. `gin --ascii` falls back to the card codes used in commands and saved hands,
+// e.g. Qh, for terminals without the symbols, and NO_COLOR turns the colour off.
+
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::Card;
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+pub fn use_ascii() {
+    ASCII.store(true, Ordering::Relaxed);
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CardStyle {
+    pub symbols: bool,
+    pub colour: bool,
+}
+
+impl CardStyle {
+    // the style chosen for this run
+    pub fn current() -> Self {
+        let ascii = ASCII.load(Ordering::Relaxed);
+        CardStyle {
+            symbols: !ascii,
+            colour: !ascii && env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
+impl Card {
+    pub fn is_red(&self) -> bool {
+        self.suit == "Hearts" || self.suit == "Diamonds"
+    }
+
+    // the card's text without any colour, e.g. Q♥, or Qh without symbols
+    pub fn face(&self, style: CardStyle) -> String {
+        if !style.symbols {
+            return self.code();
+        }
+        let rank = match self.rank.as_str() {
+            "Ten" => "10",
+            "Ace" => "A",
+            "King" => "K",
+            "Queen" => "Q",
+            "Jack" => "J",
+            _ => &self.code()[..1],
+        };
+        let suit = match self.suit.as_str() {
+            "Clubs" => "♣",
+            "Spades" => "♠",
+            "Diamonds" => "♦",
+            _ => "♥",
+        };
+        format!("{}{}", rank, suit)
+    }
+
+    // the card as it is printed, coloured if the style says so
+    pub fn show(&self, style: CardStyle) -> String {
+        let face = self.face(style);
+        if style.colour && self.is_red() {
+            format!("{}{}{}", RED, face, RESET)
+        } else {
+            face
+        }
+    }
+}
+
+// cards printed side by side in the current style
+pub fn show_cards(cards: &[Card]) -> String {
+    let style = CardStyle::current();
+    let cards: Vec<String> = cards.iter().map(|card| card.show(style)).collect();
+    cards.join(" ")
+}
@@ -0,0 +1,328 @@
+// gin connect: plays a match hosted by gin-server from this terminal.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process;
+use std::thread;
+
+use crate::command;
+use crate::correspondence::format_time;
+use crate::protocol::{
+    self, ClientMessage, ServerMessage, TableOptions, CHALLENGE_HOURS, EMOTES, PROTOCOL_VERSION,
+};
+use crate::record::{action_from_log, action_to_log};
+use crate::{Action, Phase};
+
+const COMMANDS: &str = "commands: draw, take, discard <card>, knock [card], gin [card] \
+     (cards like Qh, Q♥ or queen of hearts), q to quit";
+
+const LOBBY_COMMANDS: &str = "lobby commands: list, join [table], new [rules] [stakes] [private], \
+     invite <code>, match [rules], watch <table>";
+
+const CORRESPONDENCE_COMMANDS: &str = "correspondence commands: games, challenge <opponent> \
+     [rules] [hours], accept <game>, show <game>, post <game> <move> (e.g. post 3 discard Qh)";
+
+const USAGE: &str = "usage: gin connect <host:port> [--login | --register] [<table> | list | \
+     new [rules] [stakes] [private] | invite <code> | match [rules] | resume <session> | \
+     watch <table>]";
+
+// gin connect <host:port> [--login | --register] [<table> | list | new [rules] [stakes]
+// [private] | invite <code> | match [rules] | resume <session> | watch <table>]
+pub fn run(args: &[String]) {
+    let address = match args.first() {
+        Some(address) => address,
+        None => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    // servers with accounts need a password as well as a name
+    let login = args.get(1).map(|arg| arg.as_str());
+    let login = match login {
+        Some("--login") | Some("--register") => login,
+        _ => None,
+    };
+    let skip = if login.is_some() { 2 } else { 1 };
+    // any open table unless told otherwise
+    let words: Vec<&str> = args[skip..].iter().map(|arg| arg.as_str()).collect();
+    let seat = match words.as_slice() {
+        [] => ClientMessage::Join(None),
+        [table] if table.parse::<u32>().is_ok() => ClientMessage::Join(table.parse().ok()),
+        words => match lobby_command(words) {
+            Some(message) => message,
+            None => {
+                println!("{}", USAGE);
+                return;
+            }
+        },
+    };
+    let mut stream = match TcpStream::connect(address) {
+        Ok(stream) => stream,
+        Err(e) => {
+            println!("could not connect to {}: {}", address, e);
+            return;
+        }
+    };
+    let reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    send(
+        &mut stream,
+        &ClientMessage::Hello(PROTOCOL_VERSION, protocol::features()),
+    );
+    // a resumed seat already has a name
+    if !matches!(seat, ClientMessage::Resume(_)) {
+        println!("Your name: ");
+        let mut name = String::new();
+        io::stdin().read_line(&mut name).unwrap();
+        let name = String::from(name.trim());
+        let message = match login {
+            Some(login) => {
+                println!("Your password: ");
+                let mut password = String::new();
+                io::stdin().read_line(&mut password).unwrap();
+                let password = String::from(password.trim_end_matches(['\r', '\n']));
+                if login == "--register" {
+                    ClientMessage::Register(name, password)
+                } else {
+                    ClientMessage::Login(name, password)
+                }
+            }
+            None => ClientMessage::Name(name),
+        };
+        send(&mut stream, &message);
+    }
+    send(&mut stream, &seat);
+
+    let address = address.clone();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            match ServerMessage::from_line(&line) {
+                Ok(message) => display(&message, &address),
+                Err(e) => println!("{}", e),
+            }
+        }
+        println!("disconnected from the server");
+        process::exit(0);
+    });
+
+    println!("{}", COMMANDS);
+    println!("{}", LOBBY_COMMANDS);
+    println!("{}", CORRESPONDENCE_COMMANDS);
+    print_chat_commands();
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match line.trim() {
+            "" => continue,
+            "q" => break,
+            "help" => {
+                println!("{}", COMMANDS);
+                println!("{}", LOBBY_COMMANDS);
+                println!("{}", CORRESPONDENCE_COMMANDS);
+                print_chat_commands();
+            }
+            command => match (moves(command), lobby_command(&words)) {
+                (Some(actions), _) => {
+                    for action in actions {
+                        send(&mut stream, &ClientMessage::Play(action));
+                    }
+                }
+                (_, Some(message)) => send(&mut stream, &message),
+                _ => match chat_command(command) {
+                    Some(message) => send(&mut stream, &message),
+                    None => println!("Invalid command. {}", COMMANDS),
+                },
+            },
+        }
+    }
+}
+
+// a move typed as a command, or as it is written in a saved hand
+fn moves(line: &str) -> Option<Vec<Action>> {
+    match command::parse(line)
+        .ok()
+        .and_then(|command| command.actions())
+    {
+        Some(actions) => Some(actions),
+        None => action_from_log(line).ok().map(|action| vec![action]),
+    }
+}
+
+// finding a table, from the command line or before sitting down
+fn lobby_command(words: &[&str]) -> Option<ClientMessage> {
+    match words {
+        ["list"] => Some(ClientMessage::List),
+        ["join"] => Some(ClientMessage::Join(None)),
+        ["join", table] => table
+            .parse()
+            .ok()
+            .map(|table| ClientMessage::Join(Some(table))),
+        ["invite", code] => Some(ClientMessage::Invite(String::from(*code))),
+        ["resume", session] => Some(ClientMessage::Resume(String::from(*session))),
+        ["match"] => Some(ClientMessage::Match(String::from("Standard"))),
+        ["match", rules] => Some(ClientMessage::Match(String::from(*rules))),
+        ["watch", table] => table.parse().ok().map(ClientMessage::Watch),
+        ["accept", game] => game.parse().ok().map(ClientMessage::Accept),
+        ["games"] => Some(ClientMessage::Games),
+        ["show", game] => game.parse().ok().map(ClientMessage::Show),
+        ["post", game, action @ ..] => match (game.parse(), action_from_log(&action.join(" "))) {
+            (Ok(game), Ok(action)) => Some(ClientMessage::Post(game, action)),
+            _ => None,
+        },
+        // the rules and hours can come in either order
+        ["challenge", opponent, options @ ..] => {
+            let mut rules = String::from("Standard");
+            let mut hours = CHALLENGE_HOURS;
+            for option in options.iter() {
+                match option.parse() {
+                    Ok(value) => hours = value,
+                    Err(_) => rules = String::from(*option),
+                }
+            }
+            Some(ClientMessage::Challenge(
+                String::from(*opponent),
+                rules,
+                hours,
+            ))
+        }
+        // the options can come in any order
+        ["new", options @ ..] => {
+            let mut table = TableOptions::standard();
+            for option in options.iter() {
+                if *option == "private" {
+                    table.private = true;
+                } else if let Ok(stakes) = option.parse() {
+                    table.stakes = stakes;
+                } else {
+                    table.rules = String::from(*option);
+                }
+            }
+            Some(ClientMessage::Create(table))
+        }
+        _ => None,
+    }
+}
+
+fn print_chat_commands() {
+    println!(
+        "chat commands: say <text>, emote <{}>, mute, unmute",
+        EMOTES.join("|")
+    );
+}
+
+// talking to the table
+fn chat_command(line: &str) -> Option<ClientMessage> {
+    if let Some(text) = line.strip_prefix("say ") {
+        return Some(ClientMessage::Chat(String::from(text.trim())));
+    }
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["emote", emote] => Some(ClientMessage::Emote(String::from(*emote))),
+        ["mute"] => Some(ClientMessage::Mute(true)),
+        ["unmute"] => Some(ClientMessage::Mute(false)),
+        _ => None,
+    }
+}
+
+fn send(stream: &mut TcpStream, message: &ClientMessage) {
+    if writeln!(stream, "{}", message.to_line()).is_err() {
+        println!("disconnected from the server");
+        process::exit(0);
+    }
+}
+
+fn display(message: &ServerMessage, address: &str) {
+    match message {
+        // the server speaks this build's version and says if it lacks a feature asked for
+        ServerMessage::Welcome(_, _) => {}
+        ServerMessage::Account(name, _) => println!("logged in as {}", name),
+        ServerMessage::Info(text) => println!("{}", text),
+        ServerMessage::Error(text) => println!("Invalid move: {}", text),
+        ServerMessage::Table(table) => {
+            println!("Waiting for an opponent at table {}", table)
+        }
+        ServerMessage::Session(session) => println!(
+            "If you lose your connection, take your seat back with: gin connect {} resume {}",
+            address, session
+        ),
+        ServerMessage::Invite(code) => {
+            println!("The table is invite only, its code is {}", code)
+        }
+        ServerMessage::Tables(tables) => {
+            if tables.is_empty() {
+                println!("There are no open tables");
+            }
+            for table in tables.iter() {
+                println!(
+                    "Table {} - {} is waiting ({} rules, stakes of {})",
+                    table.table, table.host, table.rules, table.stakes
+                );
+            }
+        }
+        ServerMessage::Games(games) => {
+            if games.is_empty() {
+                println!("You have no correspondence games");
+            }
+            for game in games.iter() {
+                if game.turn.is_empty() {
+                    println!("Game {} - against {}, over", game.game, game.opponent);
+                } else if game.deadline == 0 {
+                    println!(
+                        "Game {} - against {}, waiting for {} to accept",
+                        game.game, game.opponent, game.turn
+                    );
+                } else {
+                    println!(
+                        "Game {} - against {}, {} to play by {}",
+                        game.game,
+                        game.opponent,
+                        game.turn,
+                        format_time(game.deadline)
+                    );
+                }
+            }
+        }
+        ServerMessage::View(view) => {
+            println!("==========");
+            view.display();
+            match view.phase {
+                Phase::Draw if view.turn == view.player => {
+                    println!("Your turn: draw from the stock or take the discard")
+                }
+                Phase::Discard if view.turn == view.player => {
+                    println!("Your turn: discard <card>, or knock or gin before discarding")
+                }
+                Phase::Draw | Phase::Discard => println!("Waiting for {}...", view.turn),
+                _ => {}
+            }
+        }
+        ServerMessage::Watched(view) => {
+            println!("==========");
+            view.display();
+            if view.phase == Phase::Draw || view.phase == Phase::Discard {
+                println!("{} to play", view.turn);
+            }
+        }
+        ServerMessage::Moved(player, action) => {
+            println!("{} played {}", player, action_to_log(action))
+        }
+        ServerMessage::Chat(player, text) => println!("{}: {}", player, text),
+        ServerMessage::Emote(player, emote) => println!("{} sends {}", player, emote),
+        ServerMessage::Result(winner, points) => {
+            if winner.is_empty() {
+                println!("The stock has run out, the hand is void.");
+            } else {
+                println!("player {} scored {} points", winner, points);
+            }
+        }
+    }
+}
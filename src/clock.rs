@@ -0,0 +1,193 @@
+// Time controls for server matches: a limit on each turn, a bank of time for the whole match
+// that each turn is taken from with an increment added back, or both. A turn runs from the
+// player's draw to their discard, and the clock stops while the game is paused for a player
+// who has lost their connection.
+
+use std::time::{Duration, Instant};
+
+use crate::{meld, Action, Card, GinGame, Move, Phase, RANK_VALUES};
+
+// what becomes of a player who runs out of time
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Timeout {
+    // their turn is played for them: a draw from the stock and their highest deadwood thrown
+    Play,
+    // they lose the hand
+    Forfeit,
+}
+
+#[derive(Clone, Copy)]
+pub struct TimeControl {
+    // the longest any one turn can take
+    pub per_move: Option<Duration>,
+    // each player's time for the whole match, and what is added back after each of their turns
+    pub bank: Option<Duration>,
+    pub increment: Duration,
+    pub timeout: Timeout,
+}
+
+impl TimeControl {
+    // no limits at all
+    pub fn untimed() -> Self {
+        TimeControl {
+            per_move: None,
+            bank: None,
+            increment: Duration::ZERO,
+            timeout: Timeout::Play,
+        }
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.per_move.is_some() || self.bank.is_some()
+    }
+
+    // e.g. "30 seconds a turn, 300 seconds a match plus 5 a turn"
+    pub fn describe(&self) -> String {
+        let mut limits = Vec::new();
+        if let Some(per_move) = self.per_move {
+            limits.push(format!("{} seconds a turn", per_move.as_secs_f64()));
+        }
+        if let Some(bank) = self.bank {
+            limits.push(format!(
+                "{} seconds a match plus {} a turn",
+                bank.as_secs_f64(),
+                self.increment.as_secs_f64()
+            ));
+        }
+        let timeout = match self.timeout {
+            Timeout::Play => "has their turn played for them",
+            Timeout::Forfeit => "forfeits the hand",
+        };
+        format!(
+            "turns are timed, {}; a player who runs out of time {}",
+            limits.join(", "),
+            timeout
+        )
+    }
+}
+
+// both players' time at a table
+pub struct Clock {
+    control: TimeControl,
+    // what is left of each player's bank
+    banks: [Duration; 2],
+    // the seat whose turn is running and when it started, moved on by any pauses
+    running: Option<(usize, Instant)>,
+    paused: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let bank = control.bank.unwrap_or_default();
+        Clock {
+            control,
+            banks: [bank, bank],
+            running: None,
+            paused: None,
+        }
+    }
+
+    // starts the seat's turn, unless it is already running
+    pub fn turn(&mut self, seat: usize) {
+        if self.running.is_some_and(|(running, _)| running == seat) {
+            return;
+        }
+        self.stop();
+        if self.control.is_timed() {
+            self.running = Some((seat, Instant::now()));
+        }
+    }
+
+    // ends the running turn, taking it from the player's bank and adding the increment
+    pub fn stop(&mut self) {
+        self.resume();
+        if let Some((seat, started)) = self.running.take() {
+            if self.control.bank.is_some() {
+                self.banks[seat] =
+                    self.banks[seat].saturating_sub(started.elapsed()) + self.control.increment;
+            }
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused.is_none() {
+            self.paused = Some(Instant::now());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let (Some(paused), Some((seat, started))) = (self.paused.take(), self.running) {
+            self.running = Some((seat, started + paused.elapsed()));
+        }
+    }
+
+    // when the running turn runs out of time, if it can while the clock is going
+    pub fn deadline(&self) -> Option<Instant> {
+        if self.paused.is_some() {
+            return None;
+        }
+        let (seat, started) = self.running?;
+        let bank = self.control.bank.map(|_| self.banks[seat]);
+        let limit = match (self.control.per_move, bank) {
+            (Some(per_move), Some(bank)) => per_move.min(bank),
+            (limit, None) | (None, limit) => limit?,
+        };
+        Some(started + limit)
+    }
+
+    // the seat that has run out of time, which ends their turn
+    pub fn expire(&mut self) -> Option<usize> {
+        let seat = self.running.map(|(seat, _)| seat);
+        self.stop();
+        seat
+    }
+
+    pub fn policy(&self) -> Timeout {
+        self.control.timeout
+    }
+}
+
+impl GinGame {
+    // a move for a player who has run out of time: a draw from the stock, then the highest
+    // card of their deadwood, or the highest card they are allowed to throw
+    pub fn timeout_action(&self) -> Action {
+        if self.phase == Phase::Draw {
+            return Action::DrawStock;
+        }
+        let hand = &self.current_player().hand;
+        let highest_first = |cards: &[Card]| {
+            let mut cards = cards.to_vec();
+            cards.sort_by_key(|card| -RANK_VALUES.get(&card.rank).unwrap());
+            cards
+        };
+        let (_, deadwood) = meld::best_melds(hand);
+        let mut candidates = highest_first(&deadwood);
+        candidates.extend(highest_first(hand));
+        candidates
+            .into_iter()
+            .map(Action::Discard)
+            .find(|action| {
+                self.check_rules(&Move {
+                    player: self.current_turn.clone(),
+                    action: action.clone(),
+                })
+                .is_ok()
+            })
+            .unwrap_or(Action::Discard(hand[0].clone()))
+    }
+
+    // ends the hand with the player losing it; the opponent scores the player's deadwood as if
+    // they had gone down with none
+    pub fn forfeit_hand(&mut self, player: &str) {
+        let (forfeiting, opponent) = if player == self.first_player.name {
+            (&self.first_player, &self.second_player)
+        } else {
+            (&self.second_player, &self.first_player)
+        };
+        let (_, deadwood) = meld::best_melds(&forfeiting.hand);
+        let points = meld::deadwood(&deadwood);
+        let winner = opponent.name.clone();
+        self.set_score(points, winner);
+        self.phase = Phase::Over;
+    }
+}
@@ -0,0 +1,527 @@
+// The commands typed to play at the terminal: "draw stock", "take", "discard QH", "knock 7c",
+// "meld 3h 4h 5h", "layoff 9s", "done", "undo", "sort suit", "pin 7h 8h" and "help". Cards are
+// written as their codes (Qh, 10h), with a suit symbol (Q♥) or in words (queen of hearts), in
+// any case. Tab completes the command words and the cards in hand.
+
+#[cfg(feature = "terminal")]
+use std::process;
+
+#[cfg(feature = "terminal")]
+use rustyline::completion::Completer;
+#[cfg(feature = "terminal")]
+use rustyline::highlight::Highlighter;
+#[cfg(feature = "terminal")]
+use rustyline::hint::Hinter;
+#[cfg(feature = "terminal")]
+use rustyline::history::DefaultHistory;
+#[cfg(feature = "terminal")]
+use rustyline::validate::Validator;
+#[cfg(feature = "terminal")]
+use rustyline::{Context, Editor, Helper};
+
+use crate::cards::CardStyle;
+use crate::hand::HandOrder;
+use crate::{meld, Action, Card, GinGame, Move, Phase, SUITS};
+
+pub const HELP: &str = "commands:
+  draw stock (or draw)  draws the top card of the stock
+  take                  takes the top card of the discard pile
+  discard <card>        discards a card, ending the turn
+  knock [card]          knocks, discarding the card if one is given
+  gin [card]            calls gin, discarding the card if one is given
+  meld <cards>          lays down a set or run once someone has gone down
+  layoff <card>         lays a card off on the opponent's melds
+  done                  finishes laying down
+  undo                  takes back a knock, gin, meld or lay off
+  sort suit|rank        shows the hand by suit or by rank, sort arrival undoes it
+  pin <cards>           shows these cards first, in this order
+cards are written like Qh, 10h, Q♥ or queen of hearts";
+
+#[cfg(feature = "terminal")]
+const COMMANDS: [&str; 12] = [
+    "draw", "take", "discard", "knock", "gin", "meld", "layoff", "done", "undo", "sort", "pin",
+    "help",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    DrawStock,
+    Take,
+    Discard(Card),
+    // going down, with the discard that goes with it if one is given
+    Knock(Option<Card>),
+    Gin(Option<Card>),
+    Meld(Vec<Card>),
+    LayOff(Card),
+    Done,
+    Undo,
+    // how the hand is shown, which any phase allows
+    Sort(HandOrder),
+    Help,
+}
+
+impl Command {
+    // the moves the command makes during a turn, if it is one that does
+    pub fn actions(&self) -> Option<Vec<Action>> {
+        let going_down = |action: Action, card: &Option<Card>| {
+            let mut actions = vec![action];
+            actions.extend(card.clone().map(Action::Discard));
+            actions
+        };
+        match self {
+            Command::DrawStock => Some(vec![Action::DrawStock]),
+            Command::Take => Some(vec![Action::DrawDiscard]),
+            Command::Discard(card) => Some(vec![Action::Discard(card.clone())]),
+            Command::Knock(card) => Some(going_down(Action::Knock, card)),
+            Command::Gin(card) => Some(going_down(Action::Gin, card)),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim().to_lowercase();
+    let words: Vec<&str> = line
+        .split_whitespace()
+        .map(|word| word.trim_matches(','))
+        .filter(|word| !word.is_empty() && *word != "and")
+        .collect();
+    match words.as_slice() {
+        [] => Err(String::from("type a command, or help to see them all")),
+        ["help"] | ["?"] => Ok(Command::Help),
+        ["undo"] => Ok(Command::Undo),
+        ["done"] => Ok(Command::Done),
+        ["sort"] => Err(String::from(
+            "sort by what? sort suit, sort rank or sort arrival",
+        )),
+        ["sort", "suit" | "suits"] => Ok(Command::Sort(HandOrder::Suit)),
+        ["sort", "rank" | "ranks"] => Ok(Command::Sort(HandOrder::Rank)),
+        ["sort", "arrival"] | ["unsort"] => Ok(Command::Sort(HandOrder::Arrival)),
+        ["pin"] => Err(String::from("pin which cards? e.g. pin 7h 8h 9h")),
+        ["pin", cards @ ..] => Ok(Command::Sort(HandOrder::Pinned(parse_cards(cards)?))),
+        ["draw"] | ["draw", "stock"] | ["stock"] => Ok(Command::DrawStock),
+        ["take"] | ["draw", "discard"] => Ok(Command::Take),
+        ["discard"] => Err(String::from("discard which card? e.g. discard Qh")),
+        ["discard", card @ ..] => Ok(Command::Discard(one_card(card)?)),
+        ["knock"] => Ok(Command::Knock(None)),
+        ["knock", card @ ..] => Ok(Command::Knock(Some(one_card(card)?))),
+        ["gin"] => Ok(Command::Gin(None)),
+        ["gin", card @ ..] => Ok(Command::Gin(Some(one_card(card)?))),
+        ["meld", cards @ ..] => {
+            let cards = parse_cards(cards)?;
+            if cards.len() < 3 {
+                return Err(String::from(
+                    "a meld needs at least three cards, e.g. meld 3h 4h 5h",
+                ));
+            }
+            Ok(Command::Meld(cards))
+        }
+        ["layoff", card @ ..] | ["lay", "off", card @ ..] => {
+            if card.is_empty() {
+                return Err(String::from("lay off which card? e.g. layoff 9s"));
+            }
+            Ok(Command::LayOff(one_card(card)?))
+        }
+        [word, ..] => Err(format!(
+            "{} is not a command, type help to see them all",
+            word
+        )),
+    }
+}
+
+// a card written as a code, with a suit symbol or in words
+pub fn parse_card(text: &str) -> Result<Card, String> {
+    let text = text.trim().to_lowercase();
+    let code = match text.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [rank, "of", suit] => match suit_code(suit) {
+            Some(suit) => format!("{}{}", rank_code(rank), suit),
+            None => String::new(),
+        },
+        [code] => code
+            .replace(['♣', '♧'], "c")
+            .replace(['♠', '♤'], "s")
+            .replace(['♦', '♢'], "d")
+            .replace(['♥', '♡'], "h"),
+        _ => String::new(),
+    };
+    Card::from_code(&code).ok_or_else(|| {
+        format!(
+            "{} is not a card, write cards like Qh, 10h, Q♥ or queen of hearts",
+            text
+        )
+    })
+}
+
+// cards one after another, each either one word or three ("queen of hearts")
+fn parse_cards(words: &[&str]) -> Result<Vec<Card>, String> {
+    let mut cards = Vec::new();
+    let mut index = 0;
+    while index < words.len() {
+        let length = if words.get(index + 1) == Some(&"of") {
+            3
+        } else {
+            1
+        };
+        let end = (index + length).min(words.len());
+        cards.push(parse_card(&words[index..end].join(" "))?);
+        index = end;
+    }
+    Ok(cards)
+}
+
+fn one_card(words: &[&str]) -> Result<Card, String> {
+    let cards = parse_cards(words)?;
+    match cards.as_slice() {
+        [card] => Ok(card.clone()),
+        _ => Err(String::from("name just one card")),
+    }
+}
+
+fn rank_code(rank: &str) -> &str {
+    match rank {
+        "ace" => "a",
+        "king" => "k",
+        "queen" => "q",
+        "jack" => "j",
+        "ten" => "10",
+        "nine" => "9",
+        "eight" => "8",
+        "seven" => "7",
+        "six" => "6",
+        "five" => "5",
+        "four" => "4",
+        "three" => "3",
+        "two" => "2",
+        rank => rank,
+    }
+}
+
+// the suit's letter, for a suit name such as "hearts" or "heart"
+fn suit_code(suit: &str) -> Option<char> {
+    SUITS
+        .iter()
+        .find(|name| name.to_lowercase().trim_end_matches('s') == suit.trim_end_matches('s'))
+        .and_then(|name| name.to_lowercase().chars().next())
+}
+
+// reads commands, completing them and the cards in hand with tab
+#[cfg(feature = "terminal")]
+pub struct Prompt {
+    editor: Editor<Completion, DefaultHistory>,
+}
+
+#[cfg(feature = "terminal")]
+impl Prompt {
+    pub fn new() -> Self {
+        let mut editor = Editor::new().unwrap();
+        editor.set_helper(Some(Completion { cards: Vec::new() }));
+        Prompt { editor }
+    }
+
+    // clears the screen and its scrollback, then waits for the player to take the terminal
+    pub fn pass_to(&mut self, player: &str) {
+        print!("\x1b[2J\x1b[3J\x1b[H");
+        println!("Pass the device to {}, press enter when ready", player);
+        if self.editor.readline("").is_err() {
+            println!("goodbye");
+            process::exit(0);
+        }
+    }
+
+    // the next command, asking again until one makes sense; closing the input ends the game
+    fn read(&mut self, hand: &[Card]) -> Command {
+        let style = CardStyle::current();
+        if let Some(completion) = self.editor.helper_mut() {
+            completion.cards = hand.iter().map(|card| card.face(style)).collect();
+        }
+        loop {
+            let line = match self.editor.readline("> ") {
+                Ok(line) => line,
+                Err(_) => {
+                    println!("goodbye");
+                    process::exit(0);
+                }
+            };
+            let _ = self.editor.add_history_entry(line.as_str());
+            match parse(&line) {
+                Ok(command) => return command,
+                Err(e) => println!("Invalid command: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl Default for Prompt {
+    fn default() -> Self {
+        Prompt::new()
+    }
+}
+
+#[cfg(feature = "terminal")]
+struct Completion {
+    // the cards in hand, as they are shown
+    cards: Vec<String>,
+}
+
+#[cfg(feature = "terminal")]
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(' ').map_or(0, |space| space + 1);
+        let word = line[start..pos].to_lowercase();
+        let options: Vec<&str> = match line[..start].split_whitespace().next() {
+            None => COMMANDS.to_vec(),
+            Some("draw") => vec!["stock", "discard"],
+            Some("sort") => vec!["suit", "rank", "arrival"],
+            Some(_) => self.cards.iter().map(|card| card.as_str()).collect(),
+        };
+        let matches = options
+            .into_iter()
+            .filter(|option| option.to_lowercase().starts_with(&word))
+            .map(String::from)
+            .collect();
+        Ok((start, matches))
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+#[cfg(feature = "terminal")]
+impl Highlighter for Completion {}
+
+#[cfg(feature = "terminal")]
+impl Validator for Completion {}
+
+#[cfg(feature = "terminal")]
+impl Helper for Completion {}
+
+impl GinGame {
+    // reads commands until the player has drawn and discarded
+    #[cfg(feature = "terminal")]
+    pub fn awaiting_turn(&mut self, prompt: &mut Prompt) {
+        let player = self.current_turn.clone();
+        println!("draw stock or take the discard, then discard; knock or gin first if you can. help lists the commands.");
+        while self.current_turn == player
+            && (self.phase == Phase::Draw || self.phase == Phase::Discard)
+        {
+            let command = prompt.read(&self.current_player().hand);
+            match command {
+                Command::Help => println!("{}", HELP),
+                Command::Undo => match self.undo() {
+                    Ok(()) => println!("taken back"),
+                    Err(e) => println!("Invalid command: {}", e),
+                },
+                Command::Done => println!("a turn ends with a discard"),
+                Command::Sort(order) => self.sorted(order),
+                Command::Meld(_) | Command::LayOff(_) => {
+                    println!("Invalid move: melds are laid down once someone has gone down")
+                }
+                command => match self.turn_command(&command) {
+                    Ok(()) if self.phase == Phase::Discard => {
+                        self.current_player().display_player_hand()
+                    }
+                    Ok(()) => self.display_discard_pile(),
+                    Err(e) => println!("Invalid move: {}", e),
+                },
+            }
+        }
+    }
+
+    // reads melds, and lay offs for the player who did not go down, until the player is done
+    #[cfg(feature = "terminal")]
+    pub fn awaiting_melds(&mut self, prompt: &mut Prompt) {
+        println!(
+            "{}, lay down your melds with meld <cards>, then type done.",
+            self.current_turn
+        );
+        if !self.went_down() && !self.gin_status {
+            self.opponent().melds.display_melds();
+            println!("you can also lay off cards on these melds with layoff <card>.");
+        }
+        loop {
+            self.current_player().display_player_hand();
+            let result = match prompt.read(&self.current_player().hand) {
+                Command::Done => break,
+                Command::Help => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                Command::Sort(order) => {
+                    // the hand is shown again at the top of the loop
+                    if let Err(e) = self.sort_hand(order) {
+                        println!("Invalid command: {}", e);
+                    }
+                    continue;
+                }
+                Command::Meld(cards) => self.lay_meld(&cards),
+                Command::LayOff(card) => self.lay_off(&card),
+                Command::Undo => self.undo(),
+                _ => Err(String::from("the hand is over, lay down your melds")),
+            };
+            match result {
+                Ok(()) => self.current_player().melds.display_melds(),
+                Err(e) => println!("Invalid move: {}", e),
+            }
+        }
+    }
+
+    // lays the hand out as asked and shows it again
+    #[cfg(feature = "terminal")]
+    fn sorted(&mut self, order: HandOrder) {
+        match self.sort_hand(order) {
+            Ok(()) => self.current_player().display_player_hand(),
+            Err(e) => println!("Invalid command: {}", e),
+        }
+    }
+
+    // carries out a command during the player's turn; a knock or gin whose discard is refused
+    // is taken back
+    pub fn turn_command(&mut self, command: &Command) -> Result<(), String> {
+        let actions = match command.actions() {
+            Some(actions) => actions,
+            None => return Err(String::from("that is not a move")),
+        };
+        for (index, action) in actions.into_iter().enumerate() {
+            let player_move = Move {
+                player: self.current_turn.clone(),
+                action,
+            };
+            if let Err(e) = self.check_rules(&player_move) {
+                if index > 0 {
+                    self.undo()?;
+                }
+                return Err(e);
+            }
+            self.apply_move(player_move);
+        }
+        Ok(())
+    }
+
+    // lays down a meld from the player's hand once someone has gone down
+    pub fn lay_meld(&mut self, cards: &[Card]) -> Result<(), String> {
+        if self.phase != Phase::Showdown {
+            return Err(String::from(
+                "melds are laid down once someone has gone down",
+            ));
+        }
+        self.check_held(cards)?;
+        if !meld::is_valid(cards) {
+            return Err(format!(
+                "{} is not a set or a run",
+                crate::cards::show_cards(cards)
+            ));
+        }
+        let meld_index = self.current_player().melds.collection.len();
+        self.play(Action::NewMeld);
+        for card in cards.iter() {
+            self.play(Action::Meld(card.clone(), meld_index));
+        }
+        Ok(())
+    }
+
+    // lays a card off on the first of the opponent's melds it fits
+    pub fn lay_off(&mut self, card: &Card) -> Result<(), String> {
+        if self.phase != Phase::Showdown {
+            return Err(String::from(
+                "cards are laid off once someone has gone down",
+            ));
+        }
+        if self.gin_status {
+            return Err(String::from("there is no laying off against gin"));
+        }
+        if self.went_down() {
+            return Err(String::from("only the player who did not go down lays off"));
+        }
+        self.check_held(std::slice::from_ref(card))?;
+        let style = CardStyle::current();
+        let opponent = self.opponent();
+        match opponent
+            .melds
+            .collection
+            .iter()
+            .position(|meld| meld::extends(meld, card))
+        {
+            Some(meld_index) => {
+                self.play(Action::LayOff(card.clone(), meld_index));
+                Ok(())
+            }
+            None => Err(format!(
+                "the {} does not fit any of {}'s melds",
+                card.show(style),
+                opponent.name
+            )),
+        }
+    }
+
+    // takes back the player's last knock, gin, meld or lay off; draws and discards stand
+    pub fn undo(&mut self) -> Result<(), String> {
+        let last = match self.moves.last() {
+            Some(last) if last.player == self.current_turn => last.action.clone(),
+            _ => return Err(String::from("there is nothing of yours to take back")),
+        };
+        let (player, opponent) = if self.current_turn == self.first_player.name {
+            (&mut self.first_player, &mut self.second_player)
+        } else {
+            (&mut self.second_player, &mut self.first_player)
+        };
+        match last {
+            Action::DrawStock | Action::DrawDiscard => {
+                return Err(String::from("a card once drawn stays drawn"))
+            }
+            Action::Discard(_) => return Err(String::from("a discard cannot be taken back")),
+            Action::Knock => self.knock_status = false,
+            Action::Gin => self.gin_status = false,
+            // the whole meld goes back into the hand
+            Action::NewMeld | Action::Meld(_, _) => {
+                while let Some(Move {
+                    action: Action::Meld(card, meld_index),
+                    ..
+                }) = self.moves.last().cloned()
+                {
+                    player.melds.collection[meld_index].retain(|held| *held != card);
+                    player.hand.push(card);
+                    self.moves.pop();
+                }
+                player.melds.collection.pop();
+            }
+            Action::LayOff(card, meld_index) => {
+                opponent.melds.collection[meld_index].retain(|held| *held != card);
+                player.hand.push(card);
+            }
+        }
+        self.moves.pop();
+        Ok(())
+    }
+
+    // whether the player whose turn it is knocked or called gin
+    fn went_down(&self) -> bool {
+        self.moves.iter().any(|player_move| {
+            player_move.player == self.current_turn
+                && matches!(player_move.action, Action::Knock | Action::Gin)
+        })
+    }
+
+    fn check_held(&self, cards: &[Card]) -> Result<(), String> {
+        let hand = &self.current_player().hand;
+        let style = CardStyle::current();
+        for (index, card) in cards.iter().enumerate() {
+            if !hand.contains(card) {
+                return Err(format!("you do not hold the {}", card.show(style)));
+            }
+            if cards[..index].contains(card) {
+                return Err(format!("the {} is named twice", card.show(style)));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,498 @@
+// Correspondence play: matches played a move at a time over days rather than at one sitting.
+// The server keeps each game in memory and writes it out after every move, so games outlive a
+// restart. Whoever is to play is told so through a webhook, or in the mail stub when there is
+// none. A match starts once the challenged player accepts it, and from then on a player who
+// lets the deadline for a move pass forfeits the match.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::protocol::{self, GameSummary, MAX_CHALLENGE_HOURS};
+use crate::record::{format_date, HandRecord};
+use crate::view::PlayerView;
+use crate::{Action, GinGame, Move, Phase, RuleSet};
+
+pub struct Correspondence {
+    dir: String,
+    // where notifications are posted, rather than written to the mail stub
+    webhook: Option<String>,
+    games: Vec<CorrespondenceGame>,
+}
+
+struct CorrespondenceGame {
+    id: u64,
+    // how long each player has to make a move
+    deadline: Duration,
+    // whether the challenged player has accepted; the clock only runs once they have
+    accepted: bool,
+    // when the player to play was given the move, in seconds since the unix epoch
+    last_move: u64,
+    totals: [i32; 2],
+    // hands finished so far
+    hands: usize,
+    // set once the match is over
+    winner: Option<String>,
+    game: GinGame,
+}
+
+impl Correspondence {
+    // the games saved in the directory
+    pub fn load(dir: &str, webhook: Option<String>) -> Self {
+        let mut games = Vec::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|extension| extension != "game") {
+                    continue;
+                }
+                match CorrespondenceGame::load(&path) {
+                    Ok(game) => games.push(game),
+                    Err(e) => println!("skipping {}: {}", path.display(), e),
+                }
+            }
+        }
+        games.sort_by_key(|game| game.id);
+        Correspondence {
+            dir: String::from(dir),
+            webhook,
+            games,
+        }
+    }
+
+    // challenges the opponent to a match and returns its number. The match waits for them to
+    // accept it before anyone has to move.
+    pub fn challenge(
+        &mut self,
+        challenger: &str,
+        opponent: &str,
+        rules: &str,
+        deadline: Duration,
+    ) -> Result<u64, String> {
+        let rules = match RuleSet::named(rules) {
+            Some(rules) => rules,
+            None => return Err(format!("there are no rules called {}", rules)),
+        };
+        if !protocol::valid_name(opponent) || opponent == challenger {
+            return Err(String::from("choose another opponent"));
+        }
+        let id = self.games.last().map_or(1, |game| game.id + 1);
+        let game = CorrespondenceGame {
+            id,
+            deadline,
+            accepted: false,
+            last_move: now(),
+            totals: [0, 0],
+            hands: 0,
+            winner: None,
+            game: new_hand(challenger, opponent, rules),
+        };
+        game.save(&self.dir)?;
+        let text = format!(
+            "{} challenges you to game {} under {} rules, with {} hours a move; accept {} to start",
+            challenger,
+            id,
+            game.game.rules.name,
+            deadline.as_secs() / 3600,
+            id
+        );
+        self.games.push(game);
+        self.notify(opponent, id, text);
+        Ok(id)
+    }
+
+    // starts a match the player was challenged to, and tells whoever is to play first
+    pub fn accept(&mut self, id: u64, name: &str) -> Result<(String, PlayerView), String> {
+        let index = self.position(id, name)?;
+        let game = &mut self.games[index];
+        if game.accepted {
+            return Err(format!("game {} has already started", id));
+        }
+        if game.seat(name) != Some(1) {
+            return Err(format!(
+                "game {} is waiting for your opponent to accept",
+                id
+            ));
+        }
+        game.accepted = true;
+        game.last_move = now();
+        game.save(&self.dir)?;
+        let turn = game.game.current_turn.clone();
+        let to_turn = game.describe(&turn);
+        let text = game.describe(name);
+        let view = game.game.view(name);
+        self.notify(&turn, id, to_turn);
+        Ok((text, view))
+    }
+
+    // the player's games, oldest first
+    pub fn games_of(&self, name: &str) -> Vec<GameSummary> {
+        self.games
+            .iter()
+            .filter(|game| game.seat(name).is_some())
+            .map(|game| {
+                let ongoing = game.winner.is_none();
+                // a match waiting to be accepted is the challenged player's turn, with no deadline
+                let turn = if !ongoing {
+                    String::new()
+                } else if !game.accepted {
+                    game.names()[1].clone()
+                } else {
+                    game.game.current_turn.clone()
+                };
+                GameSummary {
+                    game: game.id,
+                    opponent: game.names()[1 - game.seat(name).unwrap()].clone(),
+                    turn,
+                    deadline: if ongoing && game.accepted {
+                        game.due()
+                    } else {
+                        0
+                    },
+                }
+            })
+            .collect()
+    }
+
+    // how the game stands and what the player can see of it
+    pub fn show(&self, id: u64, name: &str) -> Result<(String, PlayerView), String> {
+        let game = self.find(id, name)?;
+        Ok((game.describe(name), game.game.view(name)))
+    }
+
+    // plays the player's move, and tells their opponent when it is their turn
+    pub fn post(
+        &mut self,
+        id: u64,
+        name: &str,
+        action: Action,
+    ) -> Result<(String, PlayerView), String> {
+        let index = self.position(id, name)?;
+        let game = &mut self.games[index];
+        if game.winner.is_some() {
+            return Err(String::from("that match is over"));
+        }
+        if !game.accepted {
+            return Err(format!("game {} has not been accepted yet", id));
+        }
+        let player_move = Move {
+            player: String::from(name),
+            action,
+        };
+        game.game.check_rules(&player_move)?;
+        game.game.apply_move(player_move);
+        if game.game.phase == Phase::Showdown {
+            game.game.showdown();
+        }
+        let result = if game.game.phase == Phase::Over {
+            Some(game.finish_hand())
+        } else {
+            None
+        };
+        game.last_move = now();
+        game.save(&self.dir)?;
+
+        let tell = |player: &str| match &result {
+            Some(result) => format!("{}: {}", result, game.describe(player)),
+            None => game.describe(player),
+        };
+        let opponent = game.names()[1 - game.seat(name).unwrap()].clone();
+        let to_opponent = if game.winner.is_some() || game.game.current_turn == opponent {
+            Some(tell(&opponent))
+        } else {
+            None
+        };
+        let text = tell(name);
+        let view = game.game.view(name);
+        if let Some(to_opponent) = to_opponent {
+            self.notify(&opponent, id, to_opponent);
+        }
+        Ok((text, view))
+    }
+
+    // forfeits the matches of players who have let their deadline pass. A challenge nobody has
+    // accepted yet has no deadline.
+    pub fn check_deadlines(&mut self) {
+        let mut forfeits = Vec::new();
+        for game in self.games.iter_mut() {
+            if game.winner.is_some() || !game.accepted || now() < game.due() {
+                continue;
+            }
+            let late = game.game.current_turn.clone();
+            let winner = game.names()[1 - game.seat(&late).unwrap()].clone();
+            let text = format!(
+                "{} did not move in time and forfeits game {} to {}",
+                late, game.id, winner
+            );
+            game.winner = Some(winner.clone());
+            if let Err(e) = game.save(&self.dir) {
+                println!("could not save game {}: {}", game.id, e);
+            }
+            forfeits.push((game.id, [late, winner], text));
+        }
+        for (id, players, text) in forfeits {
+            for player in players.iter() {
+                self.notify(player, id, text.clone());
+            }
+        }
+    }
+
+    fn position(&self, id: u64, name: &str) -> Result<usize, String> {
+        self.games
+            .iter()
+            .position(|game| game.id == id && game.seat(name).is_some())
+            .ok_or_else(|| format!("you have no game {}", id))
+    }
+
+    fn find(&self, id: u64, name: &str) -> Result<&CorrespondenceGame, String> {
+        self.position(id, name).map(|index| &self.games[index])
+    }
+
+    // posts to the webhook, or writes to the mail stub when there is none
+    fn notify(&self, player: &str, id: u64, text: String) {
+        match &self.webhook {
+            Some(webhook) => {
+                let webhook = webhook.clone();
+                let body = json!({"player": player, "game": id, "text": text}).to_string();
+                // a slow webhook holds up nobody
+                thread::spawn(move || {
+                    if let Err(e) = post(&webhook, &body) {
+                        println!("could not notify {}: {}", webhook, e);
+                    }
+                });
+            }
+            None => {
+                let mail = format!("To: {}\nSubject: gin game {}\n\n{}\n\n", player, id, text);
+                let path = Path::new(&self.dir).join("mail.txt");
+                let written = fs::create_dir_all(&self.dir).and_then(|_| {
+                    fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&path)?
+                        .write_all(mail.as_bytes())
+                });
+                if let Err(e) = written {
+                    println!("could not write to {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+impl CorrespondenceGame {
+    fn names(&self) -> [&String; 2] {
+        [&self.game.first_player.name, &self.game.second_player.name]
+    }
+
+    fn seat(&self, name: &str) -> Option<usize> {
+        self.names().iter().position(|player| *player == name)
+    }
+
+    // when the player to play runs out of time
+    fn due(&self) -> u64 {
+        self.last_move.saturating_add(self.deadline.as_secs())
+    }
+
+    fn describe(&self, name: &str) -> String {
+        let names = self.names();
+        let opponent = names[1 - self.seat(name).unwrap_or(0)];
+        let score = format!(
+            "{} {}, {} {}",
+            names[0], self.totals[0], names[1], self.totals[1]
+        );
+        match &self.winner {
+            Some(winner) => format!(
+                "game {} against {} is over, {} won ({})",
+                self.id, opponent, winner, score
+            ),
+            None if !self.accepted => format!(
+                "game {} against {} is waiting for {} to accept",
+                self.id, opponent, names[1]
+            ),
+            None => format!(
+                "game {} against {} ({}), {} to play by {}",
+                self.id,
+                opponent,
+                score,
+                self.game.current_turn,
+                format_time(self.due())
+            ),
+        }
+    }
+
+    // saves the hand and deals the next, unless someone has won the match
+    fn finish_hand(&mut self) -> String {
+        self.hands += 1;
+        self.game.record(self.id).save(self.hands);
+        let names = [self.names()[0].clone(), self.names()[1].clone()];
+        let result = match self.seat(&self.game.score.player) {
+            Some(seat) => {
+                self.totals[seat] += self.game.score.points;
+                format!(
+                    "{} scored {} points",
+                    self.game.score.player, self.game.score.points
+                )
+            }
+            None => String::from("the stock ran out and the hand is void"),
+        };
+        let target = self.game.rules.target_score;
+        match self.totals.iter().position(|total| *total >= target) {
+            Some(seat) => self.winner = Some(names[seat].clone()),
+            None => self.game = new_hand(&names[0], &names[1], self.game.rules.clone()),
+        }
+        result
+    }
+
+    fn save(&self, dir: &str) -> Result<(), String> {
+        let mut text = format!(
+            "deadline {}\nlast_move {}\nhands {}\ntotals {} {}\n",
+            self.deadline.as_secs(),
+            self.last_move,
+            self.hands,
+            self.totals[0],
+            self.totals[1]
+        );
+        // a game saved before challenges had to be accepted has no such line and has started
+        if !self.accepted {
+            text.push_str("pending yes\n");
+        }
+        if let Some(winner) = &self.winner {
+            text.push_str(&format!("winner {}\n", winner));
+        }
+        text.push_str(&self.game.record(self.id).to_log());
+        let path = Path::new(dir).join(format!("{}.game", self.id));
+        fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, text))
+            .map_err(|e| format!("could not save {}: {}", path.display(), e))
+    }
+
+    // the game's own entries, then the hand in progress as a saved hand
+    fn load(path: &Path) -> Result<CorrespondenceGame, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let number = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("invalid number: {}", value))
+        };
+        let mut deadline = Duration::ZERO;
+        let mut last_move = 0;
+        let mut hands = 0;
+        let mut totals = [0, 0];
+        let mut winner = None;
+        let mut accepted = true;
+        let mut hand = String::new();
+        for line in text.lines() {
+            match line.split_once(' ') {
+                Some(("deadline", value)) => {
+                    let secs = number(value)?;
+                    if secs > MAX_CHALLENGE_HOURS * 3600 {
+                        return Err(format!("invalid deadline: {}", value));
+                    }
+                    deadline = Duration::from_secs(secs);
+                }
+                Some(("last_move", value)) => last_move = number(value)?,
+                Some(("hands", value)) => hands = number(value)? as usize,
+                Some(("totals", value)) => {
+                    let values: Vec<i32> = value
+                        .split_whitespace()
+                        .map(|total| {
+                            total
+                                .parse()
+                                .map_err(|_| format!("invalid totals: {}", value))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    match values.as_slice() {
+                        [first, second] => totals = [*first, *second],
+                        _ => return Err(format!("invalid totals: {}", value)),
+                    }
+                }
+                Some(("pending", _)) => accepted = false,
+                Some(("winner", value)) => winner = Some(String::from(value)),
+                _ => {
+                    hand.push_str(line);
+                    hand.push('\n');
+                }
+            }
+        }
+        let record = HandRecord::from_log(&hand)?;
+        let rules = match RuleSet::named(&record.rules) {
+            Some(rules) => rules,
+            None => return Err(format!("there are no rules called {}", record.rules)),
+        };
+        let mut game = record.play_to(record.moves.len())?;
+        game.rules = rules;
+        game.seed = record.seed;
+        Ok(CorrespondenceGame {
+            id: record.match_id,
+            deadline,
+            accepted,
+            last_move,
+            totals,
+            hands,
+            winner,
+            game,
+        })
+    }
+}
+
+fn new_hand(first: &str, second: &str, rules: RuleSet) -> GinGame {
+    let mut game = GinGame::new(String::from(first), String::from(second));
+    game.rules = rules;
+    game.decide_first_turn();
+    game.deal_starting_hands();
+    game
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// "YYYY.MM.DD HH:MM UTC" for seconds since the unix epoch
+pub fn format_time(secs: u64) -> String {
+    format!(
+        "{} {:02}:{:02} UTC",
+        format_date(secs),
+        secs % 86400 / 3600,
+        secs % 3600 / 60
+    )
+}
+
+// posts the body as JSON to an http:// address and checks it was accepted
+fn post(webhook: &str, body: &str) -> Result<(), String> {
+    let rest = match webhook.strip_prefix("http://") {
+        Some(rest) => rest,
+        None => return Err(String::from("only http:// webhooks are supported")),
+    };
+    let (host, path) = match rest.split_once('/') {
+        Some((host, path)) => (host, format!("/{}", path)),
+        None => (rest, String::from("/")),
+    };
+    let mut stream = TcpStream::connect(host).map_err(|e| e.to_string())?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )
+    .map_err(|e| e.to_string())?;
+    let mut status = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status)
+        .map_err(|e| e.to_string())?;
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("the webhook answered {}", status.trim())),
+    }
+}
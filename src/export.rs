@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use crate::record::{format_date, parse_date, HandRecord};
+use crate::stats::HandSummary;
+
+const USAGE: &str = "usage: gin export <csv|json> [--player <name>] [--from <YYYY.MM.DD>] [--to <YYYY.MM.DD>] [--rules <name>]";
+
+// gin export <csv|json> [--player <name>] [--from <date>] [--to <date>] [--rules <name>]
+pub fn run(args: &[String]) {
+    let format = match args.first().map(|arg| arg.as_str()) {
+        Some(format) if format == "csv" || format == "json" => format,
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+
+    let mut filter = ExportFilter::new();
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let value = match options.next() {
+            Some(value) => value,
+            None => {
+                println!("{}", USAGE);
+                return;
+            }
+        };
+        match option.as_str() {
+            "--player" => filter.player = Some(value.clone()),
+            "--rules" => filter.rules = Some(value.clone()),
+            "--from" | "--to" => {
+                let date = match parse_date(value) {
+                    Some(date) => date,
+                    None => {
+                        println!("invalid date: {}", value);
+                        return;
+                    }
+                };
+                if option == "--from" {
+                    filter.from = Some(date);
+                } else {
+                    // up to the end of that day
+                    filter.to = Some(date + 86400);
+                }
+            }
+            _ => {
+                println!("{}", USAGE);
+                return;
+            }
+        }
+    }
+
+    let rows = hand_rows(&HandRecord::load_all(), &filter);
+    if format == "csv" {
+        print!("{}", to_csv(&rows));
+    } else {
+        println!("{}", to_json(&rows));
+    }
+}
+
+pub struct ExportFilter {
+    pub player: Option<String>,
+    // seconds since the unix epoch, from is inclusive and to is exclusive
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub rules: Option<String>,
+}
+
+impl ExportFilter {
+    pub fn new() -> Self {
+        ExportFilter {
+            player: None,
+            from: None,
+            to: None,
+            rules: None,
+        }
+    }
+
+    pub fn includes(&self, record: &HandRecord) -> bool {
+        if let Some(player) = &self.player {
+            if record.first_player != *player && record.second_player != *player {
+                return false;
+            }
+        }
+        if let Some(rules) = &self.rules {
+            if record.rules != *rules {
+                return false;
+            }
+        }
+        if self.from.is_some_and(|from| record.played < from) {
+            return false;
+        }
+        if self.to.is_some_and(|to| record.played >= to) {
+            return false;
+        }
+        true
+    }
+}
+
+impl Default for ExportFilter {
+    fn default() -> Self {
+        ExportFilter::new()
+    }
+}
+
+// one finished hand
+pub struct HandRow {
+    pub match_id: u64,
+    pub hand: u32,
+    pub date: String,
+    pub rules: String,
+    pub seed: u64,
+    pub first_player: String,
+    pub second_player: String,
+    pub knocker: String,
+    // "knock", "undercut" or "gin"
+    pub outcome: String,
+    pub winner: String,
+    pub points: i32,
+    pub knocker_deadwood: i32,
+    pub defender_deadwood: i32,
+    pub turns: u32,
+}
+
+// records should be in the order they were played, as HandRecord::load_all returns them
+pub fn hand_rows(records: &[HandRecord], filter: &ExportFilter) -> Vec<HandRow> {
+    let mut rows = Vec::new();
+    let mut hands_in_match: HashMap<u64, u32> = HashMap::new();
+    for record in records.iter() {
+        let hand = hands_in_match.entry(record.match_id).or_insert(0);
+        *hand += 1;
+        if !filter.includes(record) {
+            continue;
+        }
+        let summary = match HandSummary::from_record(record) {
+            Some(summary) => summary,
+            None => continue,
+        };
+        let outcome = if summary.gin {
+            "gin"
+        } else if summary.undercut {
+            "undercut"
+        } else {
+            "knock"
+        };
+        rows.push(HandRow {
+            match_id: record.match_id,
+            hand: *hand,
+            date: format_date(record.played),
+            rules: record.rules.clone(),
+            seed: record.seed,
+            first_player: record.first_player.clone(),
+            second_player: record.second_player.clone(),
+            knocker: summary.knocker,
+            outcome: String::from(outcome),
+            winner: summary.winner,
+            points: summary.points,
+            knocker_deadwood: summary.knocker_deadwood,
+            defender_deadwood: summary.defender_deadwood,
+            turns: summary.turns,
+        });
+    }
+    rows
+}
+
+const COLUMNS: [&str; 14] = [
+    "match",
+    "hand",
+    "date",
+    "rules",
+    "seed",
+    "first_player",
+    "second_player",
+    "knocker",
+    "outcome",
+    "winner",
+    "points",
+    "knocker_deadwood",
+    "defender_deadwood",
+    "turns",
+];
+
+impl HandRow {
+    // values in the order of COLUMNS, each with whether it is text
+    fn values(&self) -> [(String, bool); 14] {
+        [
+            (self.match_id.to_string(), false),
+            (self.hand.to_string(), false),
+            (self.date.clone(), true),
+            (self.rules.clone(), true),
+            // seeds are written as text since most do not fit in a JSON number exactly
+            (self.seed.to_string(), true),
+            (self.first_player.clone(), true),
+            (self.second_player.clone(), true),
+            (self.knocker.clone(), true),
+            (self.outcome.clone(), true),
+            (self.winner.clone(), true),
+            (self.points.to_string(), false),
+            (self.knocker_deadwood.to_string(), false),
+            (self.defender_deadwood.to_string(), false),
+            (self.turns.to_string(), false),
+        ]
+    }
+}
+
+pub fn to_csv(rows: &[HandRow]) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for row in rows.iter() {
+        let values: Vec<String> = row
+            .values()
+            .iter()
+            .map(|(value, _)| csv_value(value))
+            .collect();
+        csv.push_str(&values.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn to_json(rows: &[HandRow]) -> String {
+    if rows.is_empty() {
+        return String::from("[]");
+    }
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let fields: Vec<String> = COLUMNS
+                .iter()
+                .zip(row.values().iter())
+                .map(|(column, (value, text))| {
+                    if *text {
+                        format!("\"{}\":{}", column, json_string(value))
+                    } else {
+                        format!("\"{}\":{}", column, value)
+                    }
+                })
+                .collect();
+            format!("  {{{}}}", fields.join(","))
+        })
+        .collect();
+    format!("[\n{}\n]", objects.join(",\n"))
+}
+
+fn csv_value(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        String::from(value)
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,100 @@
+// How a hand is laid out for its player: as the cards arrived, by suit, by rank, or in an order
+// the player pinned, with the best melds the engine finds and the deadwood they leave shown
+// under it. Only the display changes; the hand itself stays in the order it was dealt and
+// drawn, which the rules rely on.
+
+use crate::cards::{show_cards, CardStyle};
+use crate::{meld, Card, GinGame, RANK_VALUES, SUITS};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HandOrder {
+    Arrival,
+    Suit,
+    Rank,
+    // these cards first, in this order, then any others as they arrived
+    Pinned(Vec<Card>),
+}
+
+impl HandOrder {
+    pub fn arrange(&self, hand: &[Card]) -> Vec<Card> {
+        let mut cards = hand.to_vec();
+        match self {
+            HandOrder::Arrival => {}
+            HandOrder::Suit => cards.sort_by_key(|card| (suit(card), rank(card))),
+            HandOrder::Rank => cards.sort_by_key(|card| (rank(card), suit(card))),
+            HandOrder::Pinned(pinned) => {
+                cards.sort_by_key(|card| pinned.iter().position(|held| held == card));
+                let unpinned = cards
+                    .iter()
+                    .take_while(|card| !pinned.contains(card))
+                    .count();
+                cards.rotate_left(unpinned);
+            }
+        }
+        cards
+    }
+
+    // a pinned order is only for the cards it named, so the next hand goes back to arrival
+    pub fn next_hand(&self) -> HandOrder {
+        match self {
+            HandOrder::Pinned(_) => HandOrder::Arrival,
+            order => order.clone(),
+        }
+    }
+}
+
+fn suit(card: &Card) -> usize {
+    SUITS.iter().position(|suit| *suit == card.suit).unwrap()
+}
+
+fn rank(card: &Card) -> i32 {
+    *RANK_VALUES.get(&card.rank).unwrap()
+}
+
+pub fn display_hand(name: &str, hand: &[Card], order: &HandOrder) {
+    println!("{}'s hand: ", name);
+    let style = CardStyle::current();
+    for (index, card) in order.arrange(hand).iter().enumerate() {
+        // the code is what commands take
+        if style.symbols {
+            println!("{} - {} ({})", index, card.show(style), card.code());
+        } else {
+            println!("{} - {}", index, card.show(style));
+        }
+    }
+    let (melds, deadwood) = meld::best_melds(hand);
+    let melds: Vec<String> = melds.iter().map(|meld| show_cards(meld)).collect();
+    if !melds.is_empty() {
+        println!("best melds: {}", melds.join(" | "));
+    }
+    println!(
+        "deadwood: {} ({})",
+        show_cards(&deadwood),
+        meld::deadwood(&deadwood)
+    );
+    println!(" ");
+}
+
+impl GinGame {
+    // how the player whose turn it is wants their hand laid out
+    pub fn sort_hand(&mut self, order: HandOrder) -> Result<(), String> {
+        let player = if self.current_turn == self.first_player.name {
+            &mut self.first_player
+        } else {
+            &mut self.second_player
+        };
+        if let HandOrder::Pinned(cards) = &order {
+            let style = CardStyle::current();
+            for (index, card) in cards.iter().enumerate() {
+                if !player.hand.contains(card) {
+                    return Err(format!("you do not hold the {}", card.show(style)));
+                }
+                if cards[..index].contains(card) {
+                    return Err(format!("the {} is named twice", card.show(style)));
+                }
+            }
+        }
+        player.order = order;
+        Ok(())
+    }
+}
@@ -0,0 +1,962 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use phf::phf_map;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng, SeedableRng};
+
+pub mod accounts;
+pub mod api;
+pub mod bot;
+pub mod cards;
+pub mod client;
+pub mod clock;
+pub mod command;
+pub mod correspondence;
+pub mod export;
+pub mod hand;
+pub mod meld;
+pub mod notation;
+pub mod p2p;
+pub mod protocol;
+pub mod rating;
+pub mod record;
+pub mod replay;
+pub mod server;
+pub mod shuffle;
+pub mod stats;
+#[cfg(feature = "terminal")]
+pub mod tui;
+pub mod view;
+pub mod web;
+pub mod websocket;
+
+use cards::CardStyle;
+#[cfg(feature = "terminal")]
+use command::Prompt;
+use hand::HandOrder;
+use record::HandRecord;
+
+static RANK_VALUES: phf::Map<&'static str, i32> = phf_map! {
+    "Ace" => 1,
+    "King"=> 13,
+    "Queen" => 12,
+    "Jack" => 11,
+    "Ten" => 10,
+    "Nine" => 9,
+    "Eight" => 8,
+    "Seven" => 7,
+    "Six" => 6,
+    "Five" => 5,
+    "Four" => 4,
+    "Three" => 3,
+    "Two" => 2,
+};
+
+const SUITS: [&str; 4] = ["Clubs", "Spades", "Diamonds", "Hearts"];
+
+const RANKS: [&str; 13] = [
+    "Ace", "King", "Queen", "Jack", "Ten", "Nine", "Eight", "Seven", "Six", "Five", "Four",
+    "Three", "Two",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    suit: String,
+    rank: String,
+}
+
+impl Card {
+    fn reveal(&self) -> String {
+        format!("{} of {}", self.rank, self.suit)
+    }
+
+    // short form used in saved hands, e.g. "Qh" or "Tc"
+    fn code(&self) -> String {
+        let rank = match self.rank.as_str() {
+            "Ace" => "A",
+            "King" => "K",
+            "Queen" => "Q",
+            "Jack" => "J",
+            "Ten" => "T",
+            "Nine" => "9",
+            "Eight" => "8",
+            "Seven" => "7",
+            "Six" => "6",
+            "Five" => "5",
+            "Four" => "4",
+            "Three" => "3",
+            _ => "2",
+        };
+        format!("{}{}", rank, self.suit[..1].to_lowercase())
+    }
+
+    fn from_code(code: &str) -> Option<Card> {
+        let code = code.trim();
+        // the suit is the last character, which need not be a single byte
+        let (index, suit) = code.char_indices().last()?;
+        let rank = &code[..index];
+        let rank = match rank.to_uppercase().as_str() {
+            "A" => "Ace",
+            "K" => "King",
+            "Q" => "Queen",
+            "J" => "Jack",
+            "T" | "10" => "Ten",
+            "9" => "Nine",
+            "8" => "Eight",
+            "7" => "Seven",
+            "6" => "Six",
+            "5" => "Five",
+            "4" => "Four",
+            "3" => "Three",
+            "2" => "Two",
+            _ => return None,
+        };
+        let suit = SUITS.iter().find(|name| {
+            name.chars()
+                .next()
+                .is_some_and(|first| first.eq_ignore_ascii_case(&suit))
+        })?;
+        Some(Card {
+            suit: String::from(*suit),
+            rank: String::from(rank),
+        })
+    }
+}
+
+struct DiscardPile {
+    cards: Vec<Card>,
+}
+
+impl DiscardPile {
+    fn create() -> Self {
+        DiscardPile { cards: Vec::new() }
+    }
+
+    fn draw_card(&mut self, destination: &mut Vec<Card>) {
+        let drawn_card = self.cards.pop();
+        match drawn_card {
+            Some(card) => destination.push(card),
+            None => panic!("no more cards left!"),
+        };
+    }
+
+    fn discard_card(&mut self, origin: &mut Vec<Card>, card_index: usize) {
+        let card = origin.remove(card_index);
+        self.cards.push(card);
+    }
+}
+
+pub struct Deck {
+    cards: Vec<Card>,
+}
+
+impl Deck {
+    fn create() -> Self {
+        let mut cards = Vec::new();
+        for suit in SUITS {
+            for rank in RANKS {
+                let suit = String::from(suit);
+                let rank = String::from(rank);
+                cards.push(Card { suit, rank });
+            }
+        }
+        Deck { cards }
+    }
+
+    // a deck in the given order, listed from the top as 52 card codes ("Ac Kc Qc ..." or
+    // "AcKcQc..."), so a deal can be set up by hand or taken from an earlier hand
+    pub fn create_from(order: &str) -> Result<Self, String> {
+        let codes: Vec<String> = if order
+            .trim()
+            .contains(|c: char| c == ',' || c.is_whitespace())
+        {
+            order
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|code| !code.is_empty())
+                .map(String::from)
+                .collect()
+        } else {
+            let chars: Vec<char> = order.trim().chars().collect();
+            chars.chunks(2).map(|code| code.iter().collect()).collect()
+        };
+
+        let mut cards = Vec::new();
+        for code in codes.iter() {
+            match Card::from_code(code) {
+                Some(card) => cards.push(card),
+                None => return Err(format!("unknown card {}", code)),
+            }
+        }
+        // cards are drawn from the end of the deck
+        cards.reverse();
+        Deck::check_pack(&cards)?;
+        Ok(Deck { cards })
+    }
+
+    fn check_pack(cards: &[Card]) -> Result<(), String> {
+        if cards.len() != 52 {
+            return Err(format!("a deck has 52 cards, not {}", cards.len()));
+        }
+        for (index, card) in cards.iter().enumerate() {
+            if cards[..index].contains(card) {
+                return Err(format!(
+                    "the {} is in the deck twice",
+                    card.show(CardStyle::current())
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // the deck order from the top, in the form create_from reads
+    pub fn export(&self) -> String {
+        let codes: Vec<String> = self.cards.iter().rev().map(|card| card.code()).collect();
+        codes.join(" ")
+    }
+
+    fn peek_two(&self) -> (&Card, &Card) {
+        (
+            &self.cards[self.cards.len() - 1],
+            &self.cards[self.cards.len() - 2],
+        )
+    }
+
+    fn shuffle_deck(&mut self, rng: &mut StdRng) {
+        self.cards.shuffle(rng);
+    }
+
+    fn draw_card(&mut self, destination: &mut Vec<Card>) {
+        let drawn_card = self.cards.pop();
+        match drawn_card {
+            Some(card) => destination.push(card),
+            None => panic!("no more cards left!"),
+        };
+    }
+}
+
+struct Player {
+    name: String,
+    // the account the seat's player logged in as, on a server with accounts
+    account: Option<String>,
+    hand: Vec<Card>,
+    // how the hand is laid out when it is shown
+    order: HandOrder,
+    melds: Melds,
+}
+
+impl Player {
+    fn new(name: String) -> Self {
+        Player {
+            name,
+            account: None,
+            hand: Vec::new(),
+            order: HandOrder::Arrival,
+            melds: Melds::create(),
+        }
+    }
+
+    fn deadwood(&self) -> i32 {
+        meld::deadwood(&self.hand)
+    }
+
+    fn display_player_hand(&self) {
+        hand::display_hand(&self.name, &self.hand, &self.order);
+    }
+}
+
+struct GameResult {
+    points: i32,
+    player: String,
+}
+
+impl GameResult {
+    fn new() -> Self {
+        GameResult {
+            points: 0,
+            player: String::from(""),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RuleSet {
+    name: String,
+    knock_bonus: i32,
+    undercut_bonus: i32,
+    gin_bonus: i32,
+    target_score: i32,
+    // the most deadwood a player may knock with
+    knock_limit: i32,
+}
+
+impl RuleSet {
+    fn standard() -> Self {
+        RuleSet {
+            name: String::from("Standard"),
+            knock_bonus: 10,
+            undercut_bonus: 10,
+            gin_bonus: 20,
+            target_score: 100,
+            knock_limit: 10,
+        }
+    }
+
+    // a match to 50 rather than 100, for a shorter game online
+    fn quick() -> Self {
+        RuleSet {
+            name: String::from("Quick"),
+            target_score: 50,
+            ..RuleSet::standard()
+        }
+    }
+
+    fn all() -> Vec<Self> {
+        vec![RuleSet::standard(), RuleSet::quick()]
+    }
+
+    fn named(name: &str) -> Option<Self> {
+        RuleSet::all().into_iter().find(|rules| rules.name == name)
+    }
+}
+
+struct Melds {
+    collection: Vec<Vec<Card>>,
+}
+
+impl Melds {
+    fn create() -> Self {
+        Melds {
+            collection: Vec::new(),
+        }
+    }
+
+    fn create_new_meld(&mut self) {
+        self.collection.push(Vec::new());
+    }
+
+    fn display_melds(&self) {
+        for (meld_index, meld) in self.collection.iter().enumerate() {
+            println!("Meld {}: {}", meld_index, cards::show_cards(meld));
+        }
+    }
+
+    fn add_to_meld(&mut self, origin: &mut Vec<Card>, card_index: usize, meld_index: usize) {
+        let card = origin.remove(card_index);
+        if let Some(elem) = self.collection.get_mut(meld_index) {
+            elem.push(card);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    DrawStock,
+    DrawDiscard,
+    Knock,
+    Gin,
+    Discard(Card),
+    NewMeld,
+    // card from hand onto one of the player's own melds
+    Meld(Card, usize),
+    // card from hand onto one of the opponent's melds
+    LayOff(Card, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Draw,
+    Discard,
+    // someone has knocked or gone gin and the hands are being laid down
+    Showdown,
+    // the hand was scored, or the stock ran out
+    Over,
+}
+
+#[derive(Debug, Clone)]
+pub struct Move {
+    player: String,
+    action: Action,
+}
+
+#[cfg(feature = "terminal")]
+struct PlayerTotal {
+    score: i32,
+    name: String,
+}
+
+#[cfg(feature = "terminal")]
+impl PlayerTotal {
+    fn new(name: String) -> Self {
+        PlayerTotal { score: 0, name }
+    }
+}
+
+// a match between two players at the terminal
+#[cfg(feature = "terminal")]
+pub struct GinMatch {
+    id: u64,
+    rules: RuleSet,
+    // deals the first hand from this deck rather than a shuffled one
+    pub first_deal: Option<Deck>,
+    // players sharing the terminal pass it between turns, the screen cleared so neither sees
+    // the other's hand
+    pub hot_seat: bool,
+    games: Vec<GinGame>,
+    first_player_total: PlayerTotal,
+    second_player_total: PlayerTotal,
+}
+
+#[cfg(feature = "terminal")]
+impl GinMatch {
+    pub fn create_match(first_player_name: String, second_player_name: String) -> Self {
+        GinMatch {
+            id: record::new_match_id(),
+            rules: RuleSet::standard(),
+            first_deal: None,
+            hot_seat: false,
+            games: Vec::new(),
+            first_player_total: PlayerTotal::new(first_player_name.clone()),
+            second_player_total: PlayerTotal::new(second_player_name.clone()),
+        }
+    }
+
+    fn print_scores(&self) {
+        let mut first_player_total = 0;
+        let mut second_player_total = 0;
+        for game in self.games.iter() {
+            if game.score.player == self.first_player_total.name {
+                first_player_total += self.first_player_total.score;
+            } else {
+                second_player_total += self.second_player_total.score;
+            }
+        }
+        println!(
+            "{}'s current score is {}",
+            self.first_player_total.name, first_player_total
+        );
+        println!(
+            "{}'s current score is {}",
+            self.second_player_total.name, second_player_total
+        );
+    }
+
+    fn check_scores(&self) -> bool {
+        let mut first_player_total = 0;
+        let mut second_player_total = 0;
+        for game in self.games.iter() {
+            if game.score.player == self.first_player_total.name {
+                first_player_total += self.first_player_total.score;
+            } else {
+                second_player_total += self.second_player_total.score;
+            }
+            if first_player_total >= self.rules.target_score {
+                println!("{} wins!", self.first_player_total.name);
+                return true;
+            }
+            if second_player_total >= self.rules.target_score {
+                println!("{} wins!", self.second_player_total.name);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn start_match(&mut self) {
+        let mut prompt = Prompt::new();
+        while !self.check_scores() {
+            let mut game = GinGame::new(
+                self.first_player_total.name.clone(),
+                self.second_player_total.name.clone(),
+            );
+            game.rules = self.rules.clone();
+            if let Some(last) = self.games.last() {
+                game.first_player.order = last.first_player.order.next_hand();
+                game.second_player.order = last.second_player.order.next_hand();
+            }
+            game.decide_first_turn();
+            match self.first_deal.take() {
+                Some(deck) => {
+                    game.deck = deck;
+                    game.deal();
+                }
+                None => game.deal_starting_hands(),
+            }
+            game.display_discard_pile();
+
+            while game.phase == Phase::Draw {
+                if self.hot_seat {
+                    prompt.pass_to(&game.current_turn);
+                }
+                println!("{}", game.get_current_turn());
+                game.view(&game.current_turn).display();
+                game.awaiting_turn(&mut prompt);
+            }
+
+            if game.phase == Phase::Over {
+                println!("The stock has run out, the hand is void.");
+                game.record(self.id).save(self.games.len() + 1);
+                self.games.push(game);
+                continue;
+            }
+
+            game.awaiting_melds(&mut prompt);
+            game.set_next_turn();
+            if self.hot_seat {
+                prompt.pass_to(&game.current_turn);
+            }
+            game.awaiting_melds(&mut prompt);
+            game.calculate_score();
+            game.get_score();
+            game.record(self.id).save(self.games.len() + 1);
+            self.print_scores();
+            self.games.push(game);
+        }
+    }
+}
+
+pub struct GinGame {
+    first_player: Player,
+    second_player: Player,
+    deck: Deck,
+    discard_pile: DiscardPile,
+    current_turn: String,
+    phase: Phase,
+    knock_status: bool,
+    gin_status: bool,
+    score: GameResult,
+    rules: RuleSet,
+    seed: u64,
+    rng: StdRng,
+    dealt_deck: Vec<Card>,
+    first_turn: String,
+    moves: Vec<Move>,
+}
+
+impl GinGame {
+    fn new(first_player_name: String, second_player_name: String) -> Self {
+        let deck = Deck::create();
+        let discard_pile = DiscardPile::create();
+        let first_player = Player::new(first_player_name);
+        let second_player = Player::new(second_player_name);
+        let current_turn = String::from("");
+        let score = GameResult::new();
+        let seed = thread_rng().gen();
+        GinGame {
+            first_player,
+            second_player,
+            deck,
+            discard_pile,
+            current_turn,
+            phase: Phase::Draw,
+            knock_status: false,
+            gin_status: false,
+            score,
+            rules: RuleSet::standard(),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            dealt_deck: Vec::new(),
+            first_turn: String::from(""),
+            moves: Vec::new(),
+        }
+    }
+
+    fn record(&self, match_id: u64) -> HandRecord {
+        HandRecord {
+            match_id,
+            first_player: self.first_player.name.clone(),
+            second_player: self.second_player.name.clone(),
+            played: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            seed: self.seed,
+            rules: self.rules.name.clone(),
+            first_turn: self.first_turn.clone(),
+            accounts: [&self.first_player, &self.second_player]
+                .iter()
+                .filter_map(|player| player.account.clone())
+                .collect(),
+            deck: self.dealt_deck.clone(),
+            moves: self.moves.clone(),
+            chat: Vec::new(),
+            winner: self.score.player.clone(),
+            points: self.score.points,
+        }
+    }
+
+    #[cfg(feature = "terminal")]
+    fn get_score(&self) {
+        if self.score.points != 0 {
+            // self.score
+            println!(
+                "player {} scored {} points",
+                self.score.player, self.score.points
+            );
+        } else {
+            println!("game has not been completed");
+        }
+    }
+
+    fn set_score(&mut self, points: i32, player_name: String) {
+        self.score.points = points;
+        self.score.player = player_name;
+    }
+
+    fn decide_first_turn(&mut self) {
+        loop {
+            self.deck.shuffle_deck(&mut self.rng);
+
+            let (first_player_card, second_player_card) = self.deck.peek_two();
+
+            println!(
+                "{}'s card is {}",
+                self.first_player.name,
+                first_player_card.show(CardStyle::current())
+            );
+            println!(
+                "{}'s card is {}",
+                self.second_player.name,
+                second_player_card.show(CardStyle::current())
+            );
+
+            if RANK_VALUES.get(&first_player_card.rank) == RANK_VALUES.get(&second_player_card.rank)
+            {
+                println!("Draw again!");
+                println!(" ");
+            }
+
+            if RANK_VALUES.get(&first_player_card.rank) > RANK_VALUES.get(&second_player_card.rank)
+            {
+                println!("{} goes first.", self.first_player.name);
+                self.current_turn = self.first_player.name.clone();
+                break;
+            }
+            if RANK_VALUES.get(&first_player_card.rank) < RANK_VALUES.get(&second_player_card.rank)
+            {
+                println!("{} goes first.", self.second_player.name);
+                self.current_turn = self.second_player.name.clone();
+                break;
+            }
+        }
+        println!(" ");
+    }
+
+    fn deal_starting_hands(&mut self) {
+        self.deck.shuffle_deck(&mut self.rng);
+        self.deal();
+    }
+
+    // deals from the deck as it currently lies, so a recorded hand can be dealt again
+    fn deal(&mut self) {
+        self.dealt_deck = self.deck.cards.clone();
+        self.first_turn = self.current_turn.clone();
+        for _ in 0..10 {
+            self.deck.draw_card(&mut self.first_player.hand);
+            self.deck.draw_card(&mut self.second_player.hand);
+        }
+        self.deck.draw_card(&mut self.discard_pile.cards);
+    }
+
+    fn display_discard_pile(&self) {
+        println!("Top card of discard pile: ");
+        if !self.discard_pile.cards.is_empty() {
+            if self.knock_status || self.gin_status {
+                println!("Face down!");
+            } else {
+                println!(
+                    "{}",
+                    self.discard_pile.cards[self.discard_pile.cards.len() - 1]
+                        .show(CardStyle::current())
+                );
+            }
+        } else {
+            println!("Discard pile is empty!");
+        }
+        println!(" ");
+    }
+
+    fn get_current_turn(&mut self) -> &str {
+        &self.current_turn
+    }
+
+    fn current_player(&self) -> &Player {
+        if self.current_turn == self.first_player.name {
+            &self.first_player
+        } else {
+            &self.second_player
+        }
+    }
+
+    fn opponent(&self) -> &Player {
+        if self.current_turn == self.first_player.name {
+            &self.second_player
+        } else {
+            &self.first_player
+        }
+    }
+
+    // whether the move is allowed by the rules; melds are laid down by showdown rather than
+    // move by move
+    fn check_rules(&self, player_move: &Move) -> Result<(), String> {
+        if self.phase == Phase::Showdown || self.phase == Phase::Over {
+            return Err(String::from("the hand is over"));
+        }
+        if player_move.player != self.current_turn {
+            return Err(format!("it is {}'s turn", self.current_turn));
+        }
+        let hand = &self.current_player().hand;
+        match (&player_move.action, self.phase) {
+            (Action::DrawStock, Phase::Draw) => {
+                if self.deck.cards.is_empty() {
+                    return Err(String::from("the stock is empty"));
+                }
+            }
+            (Action::DrawDiscard, Phase::Draw) => {
+                if self.discard_pile.cards.is_empty() {
+                    return Err(String::from("the discard pile is empty"));
+                }
+            }
+            (Action::Knock | Action::Gin, Phase::Discard) => {
+                if self.knock_status || self.gin_status {
+                    return Err(String::from("you are already going down"));
+                }
+                let limit = if player_move.action == Action::Gin {
+                    0
+                } else {
+                    self.rules.knock_limit
+                };
+                let can_go_down = self.discard_indexes().into_iter().any(|index| {
+                    let mut rest = hand.clone();
+                    rest.remove(index);
+                    meld::deadwood(&meld::best_melds(&rest).1) <= limit
+                });
+                if !can_go_down {
+                    return Err(format!("you need {} deadwood or less to do that", limit));
+                }
+            }
+            (Action::Discard(card), Phase::Discard) => {
+                let card_index = match hand.iter().position(|c| c == card) {
+                    Some(card_index) => card_index,
+                    None => {
+                        return Err(format!(
+                            "you do not hold the {}",
+                            card.show(CardStyle::current())
+                        ))
+                    }
+                };
+                if !self.discard_indexes().contains(&card_index) {
+                    return Err(String::from(
+                        "you cannot discard the card you just picked up",
+                    ));
+                }
+                if self.knock_status || self.gin_status {
+                    let mut rest = hand.clone();
+                    rest.remove(card_index);
+                    let deadwood = meld::deadwood(&meld::best_melds(&rest).1);
+                    let limit = if self.gin_status {
+                        0
+                    } else {
+                        self.rules.knock_limit
+                    };
+                    if deadwood > limit {
+                        return Err(format!(
+                            "that leaves {} deadwood, you need {} or less",
+                            deadwood, limit
+                        ));
+                    }
+                }
+            }
+            (Action::DrawStock | Action::DrawDiscard, _) => {
+                return Err(String::from("you have already drawn"))
+            }
+            (Action::Knock | Action::Gin | Action::Discard(_), _) => {
+                return Err(String::from("you need to draw first"))
+            }
+            _ => return Err(String::from("melds are laid down at the end of the hand")),
+        }
+        Ok(())
+    }
+
+    // where the cards the player may throw are in their hand: any but the card just taken from
+    // the discard pile, which is always last in hand
+    fn discard_indexes(&self) -> Vec<usize> {
+        let hand = &self.current_player().hand;
+        let took_discard = self
+            .moves
+            .iter()
+            .rev()
+            .find(|m| matches!(m.action, Action::DrawStock | Action::DrawDiscard))
+            .is_some_and(|draw| draw.action == Action::DrawDiscard);
+        (0..hand.len())
+            .filter(|index| !took_discard || *index != hand.len() - 1)
+            .collect()
+    }
+
+    // lays down the best melds for both players, lays off what the defender can and scores
+    // the hand
+    fn showdown(&mut self) {
+        let (melds, _) = meld::best_melds(&self.current_player().hand);
+        self.lay_down(melds);
+        self.set_next_turn();
+        let (melds, _) = meld::best_melds(&self.current_player().hand);
+        self.lay_down(melds);
+        // there is no laying off against gin
+        while !self.gin_status {
+            let knocker_melds = &self.opponent().melds.collection;
+            let lay_off = self.current_player().hand.iter().find_map(|card| {
+                knocker_melds
+                    .iter()
+                    .position(|meld| meld::extends(meld, card))
+                    .map(|meld_index| (card.clone(), meld_index))
+            });
+            match lay_off {
+                Some((card, meld_index)) => self.play(Action::LayOff(card, meld_index)),
+                None => break,
+            }
+        }
+        self.calculate_score();
+    }
+
+    fn lay_down(&mut self, melds: Vec<Vec<Card>>) {
+        for meld in melds {
+            let meld_index = self.current_player().melds.collection.len();
+            self.play(Action::NewMeld);
+            for card in meld {
+                self.play(Action::Meld(card, meld_index));
+            }
+        }
+    }
+
+    fn play(&mut self, action: Action) {
+        let player = self.current_turn.clone();
+        self.apply_move(Move { player, action });
+    }
+
+    fn apply_move(&mut self, player_move: Move) {
+        let (player, opponent) = if player_move.player == self.first_player.name {
+            (&mut self.first_player, &mut self.second_player)
+        } else {
+            (&mut self.second_player, &mut self.first_player)
+        };
+        match &player_move.action {
+            Action::DrawStock => {
+                self.deck.draw_card(&mut player.hand);
+                self.phase = Phase::Discard;
+            }
+            Action::DrawDiscard => {
+                self.discard_pile.draw_card(&mut player.hand);
+                self.phase = Phase::Discard;
+            }
+            Action::Knock => self.knock_status = true,
+            Action::Gin => self.gin_status = true,
+            Action::Discard(card) => {
+                let card_index = find_card(&player.hand, card);
+                self.discard_pile.discard_card(&mut player.hand, card_index);
+                self.phase = if self.knock_status || self.gin_status {
+                    Phase::Showdown
+                } else if self.deck.cards.len() <= 2 {
+                    // the last two cards are never drawn
+                    Phase::Over
+                } else {
+                    Phase::Draw
+                };
+            }
+            Action::NewMeld => player.melds.create_new_meld(),
+            Action::Meld(card, meld_index) => {
+                let card_index = find_card(&player.hand, card);
+                player
+                    .melds
+                    .add_to_meld(&mut player.hand, card_index, *meld_index);
+            }
+            Action::LayOff(card, meld_index) => {
+                let card_index = find_card(&player.hand, card);
+                opponent
+                    .melds
+                    .add_to_meld(&mut player.hand, card_index, *meld_index);
+            }
+        }
+        let passes_turn = matches!(player_move.action, Action::Discard(_));
+        self.moves.push(player_move);
+        if passes_turn && self.phase == Phase::Draw {
+            self.set_next_turn();
+        }
+    }
+
+    fn set_next_turn(&mut self) {
+        if self.current_turn == self.first_player.name {
+            self.current_turn = self.second_player.name.clone();
+        } else {
+            self.current_turn = self.first_player.name.clone();
+        }
+    }
+
+    fn calculate_score(&mut self) {
+        let player = self.first_player.name.clone();
+
+        let first_player_deadwood = self.first_player.deadwood();
+        let second_player_deadwood = self.second_player.deadwood();
+
+        if self.get_current_turn() == player {
+            // second_player knocked or ginned
+            if self.knock_status {
+                // check for Undercut
+                if second_player_deadwood >= first_player_deadwood {
+                    println!("UNDERCUT!");
+                    self.set_score(
+                        second_player_deadwood - first_player_deadwood + self.rules.undercut_bonus,
+                        self.first_player.name.clone(),
+                    );
+                } else {
+                    println!("NOT UNDERCUT!");
+                    self.set_score(
+                        first_player_deadwood - second_player_deadwood + self.rules.knock_bonus,
+                        self.second_player.name.clone(),
+                    );
+                }
+            }
+            if self.gin_status {
+                // no undercut check here.
+                println!("GIN!");
+                self.set_score(
+                    first_player_deadwood - second_player_deadwood + self.rules.gin_bonus,
+                    self.second_player.name.clone(),
+                );
+            }
+        } else {
+            // first_player knocked or ginned
+            if self.knock_status {
+                // check for Undercut
+                if first_player_deadwood >= second_player_deadwood {
+                    println!("UNDERCUT!");
+                    self.set_score(
+                        first_player_deadwood - second_player_deadwood + self.rules.undercut_bonus,
+                        self.second_player.name.clone(),
+                    );
+                } else {
+                    println!("NOT UNDERCUT!");
+                    self.set_score(
+                        second_player_deadwood - first_player_deadwood + self.rules.knock_bonus,
+                        self.first_player.name.clone(),
+                    );
+                }
+            }
+            if self.gin_status {
+                // no undercut check here.
+                println!("GIN!");
+                self.set_score(
+                    second_player_deadwood - first_player_deadwood + self.rules.gin_bonus,
+                    self.first_player.name.clone(),
+                );
+            }
+        }
+        self.phase = Phase::Over;
+    }
+}
+
+fn find_card(cards: &[Card], card: &Card) -> usize {
+    match cards.iter().position(|c| c == card) {
+        Some(index) => index,
+        None => panic!("{} is not in hand!", card.reveal()),
+    }
+}
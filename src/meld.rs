@@ -0,0 +1,156 @@
+// Finding melds in a hand: sets of three or four cards of one rank and runs of three or more
+// cards of one suit. Aces are low, so a run never wraps from King to Ace.
+
+use crate::{Card, RANK_VALUES};
+
+// the card's place in a run, Ace 1 up to King 13
+fn value(card: &Card) -> i32 {
+    *RANK_VALUES.get(&card.rank).unwrap()
+}
+
+// what the card counts as deadwood: face cards count 10, aces 1, the rest their number
+pub fn points(card: &Card) -> i32 {
+    value(card).min(10)
+}
+
+pub fn deadwood(cards: &[Card]) -> i32 {
+    cards.iter().map(points).sum()
+}
+
+pub fn is_valid(cards: &[Card]) -> bool {
+    if cards.len() < 3 {
+        return false;
+    }
+    if cards.iter().all(|card| card.rank == cards[0].rank) {
+        return cards.len() <= 4;
+    }
+    if !cards.iter().all(|card| card.suit == cards[0].suit) {
+        return false;
+    }
+    let mut values: Vec<i32> = cards.iter().map(value).collect();
+    values.sort();
+    values.windows(2).all(|pair| pair[1] == pair[0] + 1)
+}
+
+// whether the card can be laid off on the meld
+pub fn extends(meld: &[Card], card: &Card) -> bool {
+    let mut extended = meld.to_vec();
+    extended.push(card.clone());
+    is_valid(&extended)
+}
+
+// the melds that leave the least deadwood, and the cards left over
+pub fn best_melds(cards: &[Card]) -> (Vec<Vec<Card>>, Vec<Card>) {
+    let candidates = candidate_melds(cards);
+    let mut best: Vec<usize> = Vec::new();
+    let mut best_deadwood = deadwood(cards);
+    let mut chosen = Vec::new();
+    search(
+        cards,
+        &candidates,
+        0,
+        0,
+        &mut chosen,
+        &mut best,
+        &mut best_deadwood,
+    );
+
+    let mut used = vec![false; cards.len()];
+    let mut melds = Vec::new();
+    for candidate in best.iter() {
+        let mut meld = Vec::new();
+        for index in candidates[*candidate].iter() {
+            used[*index] = true;
+            meld.push(cards[*index].clone());
+        }
+        meld.sort_by_key(value);
+        melds.push(meld);
+    }
+    let left_over = cards
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !used[*index])
+        .map(|(_, card)| card.clone())
+        .collect();
+    (melds, left_over)
+}
+
+// every set and run in the hand, as indexes into `cards`
+fn candidate_melds(cards: &[Card]) -> Vec<Vec<usize>> {
+    let mut candidates = Vec::new();
+
+    for (index, card) in cards.iter().enumerate() {
+        let same_rank: Vec<usize> = (0..cards.len())
+            .filter(|other| cards[*other].rank == card.rank)
+            .collect();
+        // only build each set from the first card of its rank
+        if same_rank[0] != index || same_rank.len() < 3 {
+            continue;
+        }
+        candidates.push(same_rank.clone());
+        if same_rank.len() == 4 {
+            // any three of the four, leaving the other free for a run
+            for left_out in 0..4 {
+                let mut set = same_rank.clone();
+                set.remove(left_out);
+                candidates.push(set);
+            }
+        }
+    }
+
+    for (index, card) in cards.iter().enumerate() {
+        // build each run upwards from its lowest card
+        let mut run = vec![index];
+        loop {
+            let next_value = value(&cards[*run.last().unwrap()]) + 1;
+            match (0..cards.len()).find(|other| {
+                cards[*other].suit == card.suit && value(&cards[*other]) == next_value
+            }) {
+                Some(next) => run.push(next),
+                None => break,
+            }
+            if run.len() >= 3 {
+                candidates.push(run.clone());
+            }
+        }
+    }
+    candidates
+}
+
+fn search(
+    cards: &[Card],
+    candidates: &[Vec<usize>],
+    from: usize,
+    used: u32,
+    chosen: &mut Vec<usize>,
+    best: &mut Vec<usize>,
+    best_deadwood: &mut i32,
+) {
+    let left_over: i32 = (0..cards.len())
+        .filter(|index| used & (1 << index) == 0)
+        .map(|index| points(&cards[index]))
+        .sum();
+    if left_over < *best_deadwood {
+        *best_deadwood = left_over;
+        *best = chosen.clone();
+    }
+    for candidate in from..candidates.len() {
+        let mask = candidates[candidate]
+            .iter()
+            .fold(0, |mask, index| mask | (1 << index));
+        if used & mask != 0 {
+            continue;
+        }
+        chosen.push(candidate);
+        search(
+            cards,
+            candidates,
+            candidate + 1,
+            used | mask,
+            chosen,
+            best,
+            best_deadwood,
+        );
+        chosen.pop();
+    }
+}
@@ -0,0 +1,383 @@
+// Gin notation: a compact, shareable text form of a complete hand. Tag pairs come first,
+// followed by one line per round of turns and one line per player for the showdown.
+//
+// [Match "1792368000"]
+// [Player1 "Mitch"]
+// [Player2 "Phoebe"]
+// [Date "2026.10.19"]
+// [Seed "5732949188147710623"]
+// [Rules "Standard"]
+// [FirstTurn "Mitch"]
+// [Hand1 "Ac 7d ..."]
+// [Hand2 "Ks Qs ..."]
+// [Upcard "9h"]
+// [Stock "2c 4h ..."]
+// [Result "Phoebe 23"]
+//
+// 1. S:draw-stock D:7h | P:take-upcard D:Qs K
+// Phoebe: M:3h,4h,5h M:9s,9h,9d
+// Mitch: M:Qc,Qd,Qh L:6h@0
+//
+// S draws from the stock, P takes the upcard, D discards, K knocks and G calls gin.
+// M lays down a meld and L lays a card off on the opponent's meld with that index.
+
+use std::collections::HashMap;
+
+use crate::record::{format_date, parse_date, HandRecord};
+use crate::{Action, Card, Move};
+
+// gin notation <file>
+pub fn run(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("usage: gin notation <file>");
+            return;
+        }
+    };
+    match HandRecord::load(path) {
+        Ok(record) => print!("{}", write(&record)),
+        Err(e) => println!("{}", e),
+    }
+}
+
+pub fn write(record: &HandRecord) -> String {
+    let mut text = String::new();
+    let date = if record.played == 0 {
+        String::from("????.??.??")
+    } else {
+        format_date(record.played)
+    };
+    let result = if record.winner.is_empty() {
+        String::from("*")
+    } else {
+        format!("{} {}", record.winner, record.points)
+    };
+    let (hand1, hand2, upcard, stock) = split_deal(&record.deck);
+    for (tag, value) in [
+        ("Match", record.match_id.to_string()),
+        ("Player1", record.first_player.clone()),
+        ("Player2", record.second_player.clone()),
+        ("Date", date),
+        ("Seed", record.seed.to_string()),
+        ("Rules", record.rules.clone()),
+        ("FirstTurn", record.first_turn.clone()),
+        ("Hand1", codes(&hand1)),
+        ("Hand2", codes(&hand2)),
+        ("Upcard", codes(&upcard)),
+        ("Stock", codes(&stock)),
+        ("Result", result),
+    ] {
+        text.push_str(&format!("[{} \"{}\"]\n", tag, value));
+    }
+    text.push('\n');
+
+    let (turns, showdown) = group_moves(&record.moves);
+    for (round, pair) in turns.chunks(2).enumerate() {
+        let halves: Vec<String> = pair
+            .iter()
+            .map(|(_, actions)| write_turn(actions))
+            .collect();
+        text.push_str(&format!("{}. {}\n", round + 1, halves.join(" | ")));
+    }
+
+    let mut meld_counts: HashMap<&str, usize> = HashMap::new();
+    for (player, actions) in showdown.iter() {
+        let first_meld = *meld_counts.get(player).unwrap_or(&0);
+        let mut melds: Vec<Vec<String>> = Vec::new();
+        let mut lay_offs: Vec<String> = Vec::new();
+        for action in actions.iter() {
+            match action {
+                Action::NewMeld => melds.push(Vec::new()),
+                Action::Meld(card, meld_index) => {
+                    if let Some(meld) = melds.get_mut(meld_index.saturating_sub(first_meld)) {
+                        meld.push(card.code());
+                    }
+                }
+                Action::LayOff(card, meld_index) => {
+                    lay_offs.push(format!("L:{}@{}", card.code(), meld_index))
+                }
+                _ => {}
+            }
+        }
+        meld_counts.insert(player, first_meld + melds.len());
+
+        let mut tokens: Vec<String> = melds
+            .iter()
+            .map(|meld| format!("M:{}", meld.join(",")))
+            .collect();
+        tokens.extend(lay_offs);
+        text.push_str(&format!("{}: {}\n", player, tokens.join(" ")));
+    }
+    text
+}
+
+pub fn parse(text: &str) -> Result<HandRecord, String> {
+    let mut record = HandRecord::empty();
+    let mut hand1 = Vec::new();
+    let mut hand2 = Vec::new();
+    let mut upcard = Vec::new();
+    let mut stock = Vec::new();
+    let mut to_play: Option<String> = None;
+    let mut meld_counts: HashMap<String, usize> = HashMap::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let (tag, value) = parse_tag(line)?;
+            match tag {
+                "Match" => {
+                    record.match_id = match value.parse() {
+                        Ok(match_id) => match_id,
+                        Err(_) => return Err(format!("invalid match: {}", value)),
+                    };
+                }
+                "Player1" => record.first_player = String::from(value),
+                "Player2" => record.second_player = String::from(value),
+                "Date" if !value.starts_with('?') => {
+                    record.played = match parse_date(value) {
+                        Some(played) => played,
+                        None => return Err(format!("invalid date: {}", value)),
+                    };
+                }
+                "Seed" => {
+                    record.seed = match value.parse() {
+                        Ok(seed) => seed,
+                        Err(_) => return Err(format!("invalid seed: {}", value)),
+                    };
+                }
+                "Rules" => record.rules = String::from(value),
+                "FirstTurn" => record.first_turn = String::from(value),
+                "Hand1" => hand1 = parse_cards(value)?,
+                "Hand2" => hand2 = parse_cards(value)?,
+                "Upcard" => upcard = parse_cards(value)?,
+                "Stock" => stock = parse_cards(value)?,
+                "Result" if value != "*" => {
+                    let (winner, points) = match value.rsplit_once(' ') {
+                        Some(pair) => pair,
+                        None => return Err(format!("invalid result: {}", value)),
+                    };
+                    record.winner = String::from(winner);
+                    record.points = match points.parse() {
+                        Ok(points) => points,
+                        Err(_) => return Err(format!("invalid result: {}", value)),
+                    };
+                }
+                // other tags, an unknown Date or an unfinished Result are skipped
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Some((number, turns)) = line.split_once(". ") {
+            if number.chars().all(|c| c.is_ascii_digit()) {
+                for half in turns.split('|') {
+                    let player = match to_play.take() {
+                        Some(player) => player,
+                        None => record.first_turn.clone(),
+                    };
+                    if player.is_empty() {
+                        return Err(String::from("moves must come after the FirstTurn tag"));
+                    }
+                    parse_turn(&player, half, &mut record.moves)?;
+                    to_play = Some(if player == record.first_player {
+                        record.second_player.clone()
+                    } else {
+                        record.first_player.clone()
+                    });
+                }
+                continue;
+            }
+        }
+
+        let showdown = match line.strip_suffix(':') {
+            Some(player) => Some((player, "")),
+            None => line.rsplit_once(": "),
+        };
+        match showdown {
+            Some((player, tokens)) => {
+                let meld_count = meld_counts.entry(String::from(player)).or_insert(0);
+                parse_showdown(player, tokens, meld_count, &mut record.moves)?;
+            }
+            None => return Err(format!("could not read line: {}", line)),
+        }
+    }
+
+    record.deck = join_deal(&hand1, &hand2, &upcard, &stock)?;
+    record.check()?;
+    Ok(record)
+}
+
+fn codes(cards: &[Card]) -> String {
+    let codes: Vec<String> = cards.iter().map(|card| card.code()).collect();
+    codes.join(" ")
+}
+
+fn parse_cards(value: &str) -> Result<Vec<Card>, String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+        .map(|code| match Card::from_code(code) {
+            Some(card) => Ok(card),
+            None => Err(format!("unknown card {}", code)),
+        })
+        .collect()
+}
+
+fn parse_tag(line: &str) -> Result<(&str, &str), String> {
+    let inner = line.trim_start_matches('[').trim_end_matches(']');
+    match inner.split_once(' ') {
+        Some((tag, value)) => Ok((tag, value.trim().trim_matches('"'))),
+        None => Err(format!("invalid tag: {}", line)),
+    }
+}
+
+// the deck is dealt from the end: a card to each player in turn, then the upcard
+fn split_deal(deck: &[Card]) -> (Vec<Card>, Vec<Card>, Vec<Card>, Vec<Card>) {
+    let mut cards = deck.to_vec();
+    let mut hand1 = Vec::new();
+    let mut hand2 = Vec::new();
+    let mut upcard = Vec::new();
+    for _ in 0..10 {
+        hand1.extend(cards.pop());
+        hand2.extend(cards.pop());
+    }
+    upcard.extend(cards.pop());
+    cards.reverse();
+    (hand1, hand2, upcard, cards)
+}
+
+fn join_deal(
+    hand1: &[Card],
+    hand2: &[Card],
+    upcard: &[Card],
+    stock: &[Card],
+) -> Result<Vec<Card>, String> {
+    if hand1.len() != 10 || hand2.len() != 10 || upcard.len() != 1 {
+        return Err(String::from(
+            "the deal needs two hands of ten cards and an upcard",
+        ));
+    }
+    let mut deck: Vec<Card> = stock.iter().rev().cloned().collect();
+    deck.push(upcard[0].clone());
+    for index in (0..10).rev() {
+        deck.push(hand2[index].clone());
+        deck.push(hand1[index].clone());
+    }
+    Ok(deck)
+}
+
+type PlayerActions<'a> = Vec<(&'a str, Vec<&'a Action>)>;
+
+// splits the moves into turns of play and each player's part of the showdown
+fn group_moves(moves: &[Move]) -> (PlayerActions<'_>, PlayerActions<'_>) {
+    let mut turns: PlayerActions = Vec::new();
+    let mut showdown: PlayerActions = Vec::new();
+    for player_move in moves.iter() {
+        let groups = match player_move.action {
+            Action::NewMeld | Action::Meld(..) | Action::LayOff(..) => &mut showdown,
+            _ => &mut turns,
+        };
+        match groups.last_mut() {
+            Some((player, actions)) if *player == player_move.player => {
+                actions.push(&player_move.action)
+            }
+            _ => groups.push((&player_move.player, vec![&player_move.action])),
+        }
+    }
+    (turns, showdown)
+}
+
+fn write_turn(actions: &[&Action]) -> String {
+    let mut tokens = Vec::new();
+    let mut call = None;
+    for action in actions.iter() {
+        match action {
+            Action::DrawStock => tokens.push(String::from("S:draw-stock")),
+            Action::DrawDiscard => tokens.push(String::from("P:take-upcard")),
+            Action::Discard(card) => tokens.push(format!("D:{}", card.code())),
+            // knocking and going gin are written after the discard that ends the turn
+            Action::Knock => call = Some("K"),
+            Action::Gin => call = Some("G"),
+            _ => {}
+        }
+    }
+    tokens.extend(call.map(String::from));
+    tokens.join(" ")
+}
+
+fn parse_turn(player: &str, half: &str, moves: &mut Vec<Move>) -> Result<(), String> {
+    let mut actions = Vec::new();
+    let mut call = None;
+    for token in half.split_whitespace() {
+        match token {
+            "S" | "S:draw-stock" => actions.push(Action::DrawStock),
+            "P" | "P:take-upcard" => actions.push(Action::DrawDiscard),
+            "K" => call = Some(Action::Knock),
+            "G" => call = Some(Action::Gin),
+            _ => match token.strip_prefix("D:").and_then(Card::from_code) {
+                Some(card) => actions.push(Action::Discard(card)),
+                None => return Err(format!("unknown move {}", token)),
+            },
+        }
+    }
+    // the game asks for the knock before the card is discarded
+    if let Some(call) = call {
+        let discard = actions
+            .iter()
+            .position(|action| matches!(action, Action::Discard(_)))
+            .unwrap_or(actions.len());
+        actions.insert(discard, call);
+    }
+    for action in actions {
+        moves.push(Move {
+            player: String::from(player),
+            action,
+        });
+    }
+    Ok(())
+}
+
+fn parse_showdown(
+    player: &str,
+    tokens: &str,
+    meld_count: &mut usize,
+    moves: &mut Vec<Move>,
+) -> Result<(), String> {
+    let mut push = |action| {
+        moves.push(Move {
+            player: String::from(player),
+            action,
+        })
+    };
+    for token in tokens.split_whitespace() {
+        if let Some(cards) = token.strip_prefix("M:") {
+            push(Action::NewMeld);
+            for card in parse_cards(cards)? {
+                push(Action::Meld(card, *meld_count));
+            }
+            *meld_count += 1;
+        } else if let Some(lay_off) = token.strip_prefix("L:") {
+            let (code, meld_index) = match lay_off.split_once('@') {
+                Some(pair) => pair,
+                None => return Err(format!("invalid lay off {}", token)),
+            };
+            let card = match Card::from_code(code) {
+                Some(card) => card,
+                None => return Err(format!("unknown card {}", code)),
+            };
+            let meld_index = match meld_index.parse() {
+                Ok(meld_index) => meld_index,
+                Err(_) => return Err(format!("invalid lay off {}", token)),
+            };
+            push(Action::LayOff(card, meld_index));
+        } else {
+            return Err(format!("unknown showdown move {}", token));
+        }
+    }
+    Ok(())
+}
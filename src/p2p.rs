@@ -0,0 +1,461 @@
+// gin p2p: plays a hand directly against another `gin p2p` with no server to deal. The deck is
+// shuffled by both peers with the mental poker shuffle in shuffle.rs, each card is unlocked
+// only for the player it is dealt to, and when the hand is over both keys are revealed and the
+// whole hand is checked and replayed before it is scored.
+//
+// Peers send each other lines of tab separated fields, the first naming the message. The host
+// deals, so shuffles first and plays second.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use num_bigint::BigUint;
+
+use crate::record::{action_from_log, action_to_log, new_match_id};
+use crate::shuffle::{self, ShuffleKey};
+use crate::{Action, Card, Deck, GinGame, Move, Phase};
+
+const COMMANDS: &str =
+    "commands: draw-stock, draw-discard, knock, gin, discard <card> (e.g. discard Qh), q to quit";
+
+const USAGE: &str = "usage: gin p2p host <address> | gin p2p join <host:port>";
+
+// peers must speak the same version of these messages
+const PEER_VERSION: u32 = 1;
+
+// the deck position of the upcard, dealt after both hands
+const UPCARD: usize = 31;
+
+enum PeerMessage {
+    // the sender's version of these messages and its name
+    Hello(u32, String),
+    // a hash of the sender's shuffle key
+    Commit(String),
+    // the deck after the sender's shuffle, top card last
+    Deck(Vec<BigUint>),
+    // the card at a deck position with the sender's lock taken off
+    Unlock(usize, BigUint),
+    Move(Action),
+    // the sender's shuffle key and the nonce in its commitment
+    Reveal(BigUint, BigUint),
+}
+
+impl PeerMessage {
+    fn to_line(&self) -> String {
+        match self {
+            PeerMessage::Hello(version, name) => format!("hello\t{}\t{}", version, name),
+            PeerMessage::Commit(hash) => format!("commit\t{}", hash),
+            PeerMessage::Deck(values) => {
+                let values: Vec<String> =
+                    values.iter().map(|value| value.to_str_radix(16)).collect();
+                format!("deck\t{}", values.join(" "))
+            }
+            PeerMessage::Unlock(position, value) => {
+                format!("unlock\t{}\t{}", position, value.to_str_radix(16))
+            }
+            PeerMessage::Move(action) => format!("move\t{}", action_to_log(action)),
+            PeerMessage::Reveal(key, nonce) => {
+                format!(
+                    "reveal\t{}\t{}",
+                    key.to_str_radix(16),
+                    nonce.to_str_radix(16)
+                )
+            }
+        }
+    }
+
+    fn from_line(line: &str) -> Result<PeerMessage, String> {
+        let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+        match fields.as_slice() {
+            ["hello", version, name] => match version.parse() {
+                Ok(version) => Ok(PeerMessage::Hello(version, String::from(*name))),
+                Err(_) => Err(format!("invalid version: {}", version)),
+            },
+            ["commit", hash] => Ok(PeerMessage::Commit(String::from(*hash))),
+            ["deck", values] => Ok(PeerMessage::Deck(
+                values.split(' ').map(number).collect::<Result<_, _>>()?,
+            )),
+            ["unlock", position, value] => match position.parse() {
+                Ok(position) => Ok(PeerMessage::Unlock(position, number(value)?)),
+                Err(_) => Err(format!("invalid position: {}", position)),
+            },
+            ["move", action] => Ok(PeerMessage::Move(action_from_log(action)?)),
+            ["reveal", key, nonce] => Ok(PeerMessage::Reveal(number(key)?, number(nonce)?)),
+            _ => Err(format!("unknown message: {}", line)),
+        }
+    }
+}
+
+fn number(hex: &str) -> Result<BigUint, String> {
+    match BigUint::parse_bytes(hex.as_bytes(), 16) {
+        Some(value) => Ok(value),
+        None => Err(format!("invalid number: {}", hex)),
+    }
+}
+
+struct Peer {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Peer {
+    fn send(&mut self, message: &PeerMessage) -> Result<(), String> {
+        match writeln!(self.writer, "{}", message.to_line()) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(String::from("the other player disconnected")),
+        }
+    }
+
+    fn receive(&mut self) -> Result<PeerMessage, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => Err(String::from("the other player disconnected")),
+            Ok(_) => PeerMessage::from_line(&line),
+        }
+    }
+
+    fn receive_unlock(&mut self) -> Result<(usize, BigUint), String> {
+        match self.receive()? {
+            PeerMessage::Unlock(position, value) => Ok((position, value)),
+            _ => Err(String::from("expected a card to be unlocked")),
+        }
+    }
+}
+
+// everything needed to check the other player once the hand is over
+struct Shuffle {
+    key: ShuffleKey,
+    commitment: String,
+    // the deck after the host's shuffle and after both
+    first_pass: Vec<BigUint>,
+    deck: Vec<BigUint>,
+    // positions the other player unlocked for us
+    unlocked: Vec<(usize, BigUint)>,
+}
+
+// gin p2p host <address> | gin p2p join <host:port>
+pub fn run(args: &[String]) {
+    let (host, address) = match args {
+        [role, address] if role == "host" || role == "join" => (role == "host", address),
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+
+    println!("Your name: ");
+    let mut name = String::new();
+    io::stdin().read_line(&mut name).unwrap();
+    let name = String::from(name.trim());
+
+    let stream = if host {
+        println!("Waiting for the other player on {}", address);
+        TcpListener::bind(address).and_then(|listener| listener.accept().map(|(stream, _)| stream))
+    } else {
+        TcpStream::connect(address)
+    };
+    let stream = match stream {
+        Ok(stream) => stream,
+        Err(e) => {
+            println!("could not connect to {}: {}", address, e);
+            return;
+        }
+    };
+    let reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let mut peer = Peer {
+        reader,
+        writer: stream,
+    };
+    if let Err(e) = play_hand(&mut peer, host, &name) {
+        println!("{}", e);
+    }
+}
+
+fn play_hand(peer: &mut Peer, host: bool, name: &str) -> Result<(), String> {
+    peer.send(&PeerMessage::Hello(PEER_VERSION, String::from(name)))?;
+    let opponent = match peer.receive()? {
+        PeerMessage::Hello(version, _) if version != PEER_VERSION => {
+            return Err(format!(
+                "the other player speaks version {} of the peer protocol, this build speaks {}",
+                version, PEER_VERSION
+            ))
+        }
+        PeerMessage::Hello(_, opponent) if opponent != name => opponent,
+        PeerMessage::Hello(_, _) => return Err(String::from("both players have the same name")),
+        _ => return Err(String::from("expected the other player's name")),
+    };
+    println!("Playing {}, shuffling...", opponent);
+    let mut shuffle = shuffle_deck(peer, host)?;
+
+    let (first, second) = if host {
+        (String::from(name), opponent.clone())
+    } else {
+        (opponent.clone(), String::from(name))
+    };
+    let mut game = GinGame::new(first, second.clone());
+    game.current_turn = second;
+    game.deck = Deck {
+        cards: vec![hidden(); 52],
+    };
+    deal(peer, &mut game, &mut shuffle, host)?;
+    println!("{} goes first.", game.current_turn);
+    println!(" ");
+
+    while game.phase == Phase::Draw || game.phase == Phase::Discard {
+        if game.current_turn == name {
+            let action = match read_action(&game, name) {
+                Some(action) => action,
+                None => return Ok(()),
+            };
+            let top = game.deck.cards.len().wrapping_sub(1);
+            peer.send(&PeerMessage::Move(action.clone()))?;
+            if action == Action::DrawStock {
+                let (position, value) = peer.receive_unlock()?;
+                if position != top {
+                    return Err(format!("expected position {} to be unlocked", top));
+                }
+                game.deck.cards[top] = shuffle::card(&shuffle.key.decrypt(&value))?;
+                shuffle.unlocked.push((position, value));
+            }
+            game.play(action);
+        } else {
+            println!("Waiting for {}...", opponent);
+            let action = match peer.receive()? {
+                PeerMessage::Move(action) => action,
+                _ => return Err(String::from("expected a move")),
+            };
+            check_opponent(&game, &action)?;
+            match &action {
+                Action::DrawStock => {
+                    let top = game.deck.cards.len() - 1;
+                    let value = shuffle.key.decrypt(&shuffle.deck[top]);
+                    peer.send(&PeerMessage::Unlock(top, value))?;
+                }
+                // a card we had not seen is one of the hidden ones in their hand
+                Action::Discard(card) => {
+                    let hand = if game.current_turn == game.first_player.name {
+                        &mut game.first_player.hand
+                    } else {
+                        &mut game.second_player.hand
+                    };
+                    if !hand.contains(card) {
+                        let index = hand.iter().position(|c| *c == hidden()).unwrap();
+                        hand[index] = card.clone();
+                    }
+                }
+                _ => {}
+            }
+            println!("{} played {}", opponent, action_to_log(&action));
+            game.play(action);
+        }
+    }
+
+    let game = check_hand(peer, game, shuffle, host)?;
+    println!("==========");
+    game.view(name).display();
+    if game.phase == Phase::Over && !(game.knock_status || game.gin_status) {
+        println!("The stock has run out, the hand is void.");
+    } else {
+        println!(
+            "player {} scored {} points",
+            game.score.player, game.score.points
+        );
+    }
+    game.record(new_match_id()).save(1);
+    Ok(())
+}
+
+// a card in the other player's hand, or in the stock, that we have not seen
+fn hidden() -> Card {
+    Card {
+        suit: String::from("?"),
+        rank: String::from("?"),
+    }
+}
+
+// both players commit to a key, then encrypt and shuffle the deck in turn
+fn shuffle_deck(peer: &mut Peer, host: bool) -> Result<Shuffle, String> {
+    let key = ShuffleKey::generate();
+    peer.send(&PeerMessage::Commit(key.commitment()))?;
+    let commitment = match peer.receive()? {
+        PeerMessage::Commit(commitment) => commitment,
+        _ => return Err(String::from("expected the other player's commitment")),
+    };
+
+    let receive_deck = |peer: &mut Peer| match peer.receive()? {
+        PeerMessage::Deck(deck) if deck.len() == 52 => Ok(deck),
+        _ => Err(String::from("expected the shuffled deck")),
+    };
+    let (first_pass, deck) = if host {
+        let first_pass = shuffle::encrypt_and_shuffle(&shuffle::card_values(), &key);
+        peer.send(&PeerMessage::Deck(first_pass.clone()))?;
+        (first_pass, receive_deck(peer)?)
+    } else {
+        let first_pass = receive_deck(peer)?;
+        let deck = shuffle::encrypt_and_shuffle(&first_pass, &key);
+        peer.send(&PeerMessage::Deck(deck.clone()))?;
+        (first_pass, deck)
+    };
+    Ok(Shuffle {
+        key,
+        commitment,
+        first_pass,
+        deck,
+        unlocked: Vec::new(),
+    })
+}
+
+// deals as GinGame::deal does, from the end of the deck: the host's cards first, then the
+// other player's, then the upcard. Each player unlocks the other's cards and the upcard.
+fn deal(
+    peer: &mut Peer,
+    game: &mut GinGame,
+    shuffle: &mut Shuffle,
+    host: bool,
+) -> Result<(), String> {
+    let dealt_to_host = |position: usize| position > UPCARD && position % 2 == 1;
+    let (mine, theirs): (Vec<usize>, Vec<usize>) =
+        (UPCARD + 1..52).partition(|position| dealt_to_host(*position) == host);
+
+    for position in theirs.iter().chain([UPCARD].iter()) {
+        let value = shuffle.key.decrypt(&shuffle.deck[*position]);
+        peer.send(&PeerMessage::Unlock(*position, value))?;
+    }
+    for _ in 0..mine.len() + 1 {
+        let (position, value) = peer.receive_unlock()?;
+        if !mine.contains(&position) && position != UPCARD {
+            return Err(format!("position {} is not dealt to you", position));
+        }
+        game.deck.cards[position] = shuffle::card(&shuffle.key.decrypt(&value))?;
+        shuffle.unlocked.push((position, value));
+    }
+    game.deal();
+    Ok(())
+}
+
+fn read_action(game: &GinGame, name: &str) -> Option<Action> {
+    loop {
+        println!("==========");
+        game.view(name).display();
+        match game.phase {
+            Phase::Draw => println!("Your turn: draw-stock or draw-discard"),
+            _ => println!("Your turn: discard <card>, or knock or gin before discarding"),
+        }
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+            return None;
+        }
+        let action = match line.trim() {
+            "q" => return None,
+            "help" => {
+                println!("{}", COMMANDS);
+                continue;
+            }
+            command => match action_from_log(command) {
+                Ok(action) => action,
+                Err(_) => {
+                    println!("Invalid command. {}", COMMANDS);
+                    continue;
+                }
+            },
+        };
+        let player_move = Move {
+            player: String::from(name),
+            action: action.clone(),
+        };
+        match game.check_rules(&player_move) {
+            Ok(_) => return Some(action),
+            Err(e) => println!("Invalid move: {}", e),
+        }
+    }
+}
+
+// the other player's hand is hidden, so only what can be seen is checked here; the rest is
+// checked once the deck is opened
+fn check_opponent(game: &GinGame, action: &Action) -> Result<(), String> {
+    let going_down = game.knock_status || game.gin_status;
+    let allowed = match (action, game.phase) {
+        (Action::DrawStock, Phase::Draw) => !game.deck.cards.is_empty(),
+        (Action::DrawDiscard, Phase::Draw) => !game.discard_pile.cards.is_empty(),
+        (Action::Knock | Action::Gin, Phase::Discard) => !going_down,
+        (Action::Discard(card), Phase::Discard) => {
+            let hand = &game.current_player().hand;
+            hand.contains(card) || hand.contains(&hidden())
+        }
+        _ => false,
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} broke the rules: {}",
+            game.current_turn,
+            action_to_log(action)
+        ))
+    }
+}
+
+// reveals both keys, checks the other player's shuffle and every card they unlocked, then
+// replays the hand from the opened deck with the rules checked and scores it
+fn check_hand(
+    peer: &mut Peer,
+    game: GinGame,
+    shuffle: Shuffle,
+    host: bool,
+) -> Result<GinGame, String> {
+    let key = &shuffle.key;
+    peer.send(&PeerMessage::Reveal(
+        key.encrypt_key().clone(),
+        key.nonce().clone(),
+    ))?;
+    let their_key = match peer.receive()? {
+        PeerMessage::Reveal(encrypt, nonce) => {
+            ShuffleKey::reveal(encrypt, nonce, &shuffle.commitment)?
+        }
+        _ => return Err(String::from("expected the other player's key")),
+    };
+
+    let (host_key, joiner_key) = if host {
+        (key, &their_key)
+    } else {
+        (&their_key, key)
+    };
+    if !shuffle::check_shuffle(&shuffle::card_values(), &shuffle.first_pass, host_key)
+        || !shuffle::check_shuffle(&shuffle.first_pass, &shuffle.deck, joiner_key)
+    {
+        return Err(String::from("the deck was not shuffled fairly"));
+    }
+    for (position, value) in shuffle.unlocked.iter() {
+        if their_key.encrypt(value) != shuffle.deck[*position] {
+            return Err(format!(
+                "the card at position {} was not unlocked honestly",
+                position
+            ));
+        }
+    }
+    let cards = shuffle::open(&shuffle.deck, [host_key, joiner_key])?;
+
+    let mut checked = GinGame::new(
+        game.first_player.name.clone(),
+        game.second_player.name.clone(),
+    );
+    checked.current_turn = game.first_turn.clone();
+    checked.deck = Deck { cards };
+    checked.deal();
+    for player_move in game.moves {
+        if let Err(e) = checked.check_rules(&player_move) {
+            return Err(format!("{} broke the rules: {}", player_move.player, e));
+        }
+        checked.apply_move(player_move);
+    }
+    if checked.phase == Phase::Showdown {
+        checked.showdown();
+    }
+    Ok(checked)
+}
@@ -0,0 +1,803 @@
+// The messages passed between gin-server and its clients. `gin connect` sends them as lines of
+// tab separated fields, the first naming the message; browsers send them as JSON over a
+// WebSocket, see docs/websocket.md. Cards are sent as codes and actions in the same form as
+// saved hands, e.g. "discard Qh".
+//
+// Every connection opens with a handshake: the client says hello with its protocol version and
+// the features it supports, and the server welcomes it with the features they share, or turns
+// it away if it cannot speak that version.
+
+use serde_json::{json, Value};
+
+use crate::hand::HandOrder;
+use crate::record::{action_from_log, action_to_log};
+use crate::view::PlayerView;
+use crate::{Action, Card, Phase, RuleSet};
+
+// the version of the protocol this build speaks, and the oldest a server still accepts
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const OLDEST_VERSION: u32 = 1;
+
+// features beyond playing a match, each of which both ends must support to use. Rule sets are
+// features too, named "rules:" and then the rule set's name.
+pub const RESUME: &str = "resume";
+pub const SPECTATE: &str = "spectate";
+pub const CORRESPONDENCE: &str = "correspondence";
+pub const CHAT: &str = "chat";
+pub const ACCOUNTS: &str = "accounts";
+
+// everything this build supports
+pub fn features() -> Vec<String> {
+    let mut features: Vec<String> = [RESUME, SPECTATE, CORRESPONDENCE, CHAT, ACCOUNTS]
+        .iter()
+        .map(|feature| String::from(*feature))
+        .collect();
+    for rules in RuleSet::all() {
+        features.push(rules_feature(&rules.name));
+    }
+    features
+}
+
+pub fn rules_feature(rules: &str) -> String {
+    format!("rules:{}", rules)
+}
+
+// how long each player has for a move in a correspondence match, unless the challenge says
+pub const CHALLENGE_HOURS: u64 = 72;
+// the longest name a player can take, in characters
+pub const MAX_NAME_LENGTH: usize = 32;
+// the most hours a challenge can give for a move, thirty days
+pub const MAX_CHALLENGE_HOURS: u64 = 720;
+
+// the emotes a player can send, the only ones the server passes on
+pub const EMOTES: [&str; 6] = [
+    "hello",
+    "good-game",
+    "well-played",
+    "thanks",
+    "oops",
+    "hurry-up",
+];
+
+#[derive(Clone)]
+pub enum ClientMessage {
+    // the first message from every client: its protocol version and features
+    Hello(u32, Vec<String>),
+    // the client's name, before it looks for a table, on a server without accounts
+    Name(String),
+    // on a server with accounts, instead of a name: a new account's name and password, an
+    // account's name and password, or a token from an earlier login
+    Register(String, String),
+    Login(String, String),
+    Authenticate(String),
+    // sent instead of a name to take back a seat after losing the connection
+    Resume(String),
+    // asks for the tables waiting for a second player
+    List,
+    // opens a new table and waits there for an opponent
+    Create(TableOptions),
+    // a table by number, or None for any open table
+    Join(Option<u32>),
+    // an invite only table by its code
+    Invite(String),
+    // waits to be matched with a player of a similar rating under the named rules
+    Match(String),
+    // watches the match at a table
+    Watch(u32),
+    // challenges the named player to a correspondence match, under the rules, with the hours
+    // each player has for a move
+    Challenge(String, String, u64),
+    // accepts a correspondence match the client was challenged to, starting it
+    Accept(u64),
+    // asks for the client's correspondence games
+    Games,
+    // asks how a correspondence game stands
+    Show(u64),
+    // a move in a correspondence game
+    Post(u64, Action),
+    // says something to the table
+    Chat(String),
+    // sends one of EMOTES to the table
+    Emote(String),
+    // stops or starts passing on the opponent's chat and emotes
+    Mute(bool),
+    Play(Action),
+}
+
+// how a new table is set up
+#[derive(Clone)]
+pub struct TableOptions {
+    pub rules: String,
+    // what each point of the winning margin is worth
+    pub stakes: u32,
+    // invite only tables are not listed and can only be joined with their code
+    pub private: bool,
+}
+
+impl TableOptions {
+    pub fn standard() -> Self {
+        TableOptions {
+            rules: String::from("Standard"),
+            stakes: 1,
+            private: false,
+        }
+    }
+}
+
+// a table in the lobby's list
+#[derive(Clone)]
+pub struct OpenTable {
+    pub table: u32,
+    pub host: String,
+    pub rules: String,
+    pub stakes: u32,
+}
+
+// one of a player's correspondence games
+#[derive(Clone)]
+pub struct GameSummary {
+    pub game: u64,
+    pub opponent: String,
+    // empty once the match is over, and the challenged player until they accept it
+    pub turn: String,
+    // when the player to play runs out of time, in seconds since the unix epoch; 0 while the
+    // match waits to be accepted
+    pub deadline: u64,
+}
+
+#[derive(Clone)]
+pub enum ServerMessage {
+    // the answer to hello: the protocol version to speak and the features both ends support
+    Welcome(u32, Vec<String>),
+    // the account the client has logged in as, and a token to log in with next time
+    Account(String, String),
+    Info(String),
+    // a message from the client was refused
+    Error(String),
+    // the table the client is waiting at
+    Table(u32),
+    // the token to resume the client's seat with if the connection drops
+    Session(String),
+    // the code to give an opponent for an invite only table
+    Invite(String),
+    Tables(Vec<OpenTable>),
+    Games(Vec<GameSummary>),
+    // everything the receiving player can see
+    View(PlayerView),
+    // everything one of the players can see, for a spectator shown the hands
+    Watched(PlayerView),
+    // a move by the opponent, or by either player for a spectator, as it was seen at the table
+    Moved(String, Action),
+    // what a player said at the table, and who said it
+    Chat(String, String),
+    Emote(String, String),
+    // an empty winner means the stock ran out
+    Result(String, i32),
+}
+
+impl ClientMessage {
+    // the features both ends must have agreed to for the message to be accepted
+    pub fn needs(&self) -> Vec<String> {
+        match self {
+            ClientMessage::Resume(_) => vec![String::from(RESUME)],
+            ClientMessage::Create(options) => vec![rules_feature(&options.rules)],
+            ClientMessage::Match(rules) => vec![rules_feature(rules)],
+            ClientMessage::Watch(_) => vec![String::from(SPECTATE)],
+            ClientMessage::Challenge(_, rules, _) => {
+                vec![String::from(CORRESPONDENCE), rules_feature(rules)]
+            }
+            ClientMessage::Accept(_)
+            | ClientMessage::Games
+            | ClientMessage::Show(_)
+            | ClientMessage::Post(_, _) => vec![String::from(CORRESPONDENCE)],
+            ClientMessage::Chat(_) | ClientMessage::Emote(_) | ClientMessage::Mute(_) => {
+                vec![String::from(CHAT)]
+            }
+            ClientMessage::Register(_, _)
+            | ClientMessage::Login(_, _)
+            | ClientMessage::Authenticate(_) => vec![String::from(ACCOUNTS)],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn to_line(&self) -> String {
+        match self {
+            ClientMessage::Hello(version, features) => {
+                format!("hello\t{}\t{}", version, features.join(" "))
+            }
+            ClientMessage::Name(name) => format!("name\t{}", name),
+            ClientMessage::Register(name, password) => {
+                format!("register\t{}\t{}", name, password)
+            }
+            ClientMessage::Login(name, password) => format!("login\t{}\t{}", name, password),
+            ClientMessage::Authenticate(token) => format!("authenticate\t{}", token),
+            ClientMessage::Resume(session) => format!("resume\t{}", session),
+            ClientMessage::List => String::from("list"),
+            ClientMessage::Create(options) => format!(
+                "create\t{}\t{}\t{}",
+                options.rules,
+                options.stakes,
+                if options.private { "private" } else { "open" }
+            ),
+            ClientMessage::Join(Some(table)) => format!("join\t{}", table),
+            ClientMessage::Join(None) => String::from("join"),
+            ClientMessage::Invite(code) => format!("invite\t{}", code),
+            ClientMessage::Match(rules) => format!("match\t{}", rules),
+            ClientMessage::Watch(table) => format!("watch\t{}", table),
+            ClientMessage::Challenge(opponent, rules, hours) => {
+                format!("challenge\t{}\t{}\t{}", opponent, rules, hours)
+            }
+            ClientMessage::Accept(game) => format!("accept\t{}", game),
+            ClientMessage::Games => String::from("games"),
+            ClientMessage::Show(game) => format!("show\t{}", game),
+            ClientMessage::Post(game, action) => {
+                format!("post\t{}\t{}", game, action_to_log(action))
+            }
+            ClientMessage::Chat(text) => format!("chat\t{}", text),
+            ClientMessage::Emote(emote) => format!("emote\t{}", emote),
+            ClientMessage::Mute(true) => String::from("mute"),
+            ClientMessage::Mute(false) => String::from("unmute"),
+            ClientMessage::Play(action) => format!("play\t{}", action_to_log(action)),
+        }
+    }
+
+    pub fn from_line(line: &str) -> Result<ClientMessage, String> {
+        let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+        match fields.as_slice() {
+            ["hello", version, features] => match version.parse() {
+                Ok(version) => Ok(ClientMessage::Hello(
+                    version,
+                    features.split_whitespace().map(String::from).collect(),
+                )),
+                Err(_) => Err(format!("invalid version: {}", version)),
+            },
+            ["name", name] => Ok(ClientMessage::Name(String::from(*name))),
+            ["register", name, password] => Ok(ClientMessage::Register(
+                String::from(*name),
+                String::from(*password),
+            )),
+            ["login", name, password] => Ok(ClientMessage::Login(
+                String::from(*name),
+                String::from(*password),
+            )),
+            ["authenticate", token] => Ok(ClientMessage::Authenticate(String::from(*token))),
+            ["resume", session] => Ok(ClientMessage::Resume(String::from(*session))),
+            ["list"] => Ok(ClientMessage::List),
+            ["create"] => Ok(ClientMessage::Create(TableOptions::standard())),
+            ["create", rules, stakes, private] => match stakes.parse() {
+                Ok(stakes) => Ok(ClientMessage::Create(TableOptions {
+                    rules: String::from(*rules),
+                    stakes,
+                    private: *private == "private",
+                })),
+                Err(_) => Err(format!("invalid stakes: {}", stakes)),
+            },
+            ["join"] => Ok(ClientMessage::Join(None)),
+            ["join", table] => match table.parse() {
+                Ok(table) => Ok(ClientMessage::Join(Some(table))),
+                Err(_) => Err(format!("invalid table: {}", table)),
+            },
+            ["invite", code] => Ok(ClientMessage::Invite(String::from(*code))),
+            ["match"] => Ok(ClientMessage::Match(String::from("Standard"))),
+            ["match", rules] => Ok(ClientMessage::Match(String::from(*rules))),
+            ["watch", table] => match table.parse() {
+                Ok(table) => Ok(ClientMessage::Watch(table)),
+                Err(_) => Err(format!("invalid table: {}", table)),
+            },
+            ["challenge", opponent, rules, hours] => match hours.parse() {
+                Ok(hours) => Ok(ClientMessage::Challenge(
+                    String::from(*opponent),
+                    String::from(*rules),
+                    check_hours(hours)?,
+                )),
+                Err(_) => Err(format!("invalid hours: {}", hours)),
+            },
+            ["accept", game] => match game.parse() {
+                Ok(game) => Ok(ClientMessage::Accept(game)),
+                Err(_) => Err(format!("invalid game: {}", game)),
+            },
+            ["games"] => Ok(ClientMessage::Games),
+            ["show", game] => match game.parse() {
+                Ok(game) => Ok(ClientMessage::Show(game)),
+                Err(_) => Err(format!("invalid game: {}", game)),
+            },
+            ["post", game, action] => match game.parse() {
+                Ok(game) => Ok(ClientMessage::Post(game, action_from_log(action)?)),
+                Err(_) => Err(format!("invalid game: {}", game)),
+            },
+            ["chat", text] => Ok(ClientMessage::Chat(String::from(*text))),
+            ["emote", emote] => Ok(ClientMessage::Emote(String::from(*emote))),
+            ["mute"] => Ok(ClientMessage::Mute(true)),
+            ["unmute"] => Ok(ClientMessage::Mute(false)),
+            ["play", action] => Ok(ClientMessage::Play(action_from_log(action)?)),
+            _ => Err(format!("unknown message: {}", line)),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<ClientMessage, String> {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return Err(format!("invalid JSON: {}", e)),
+        };
+        let field = |name: &str| message.get(name).and_then(|value| value.as_str());
+        match field("type") {
+            Some("hello") => match message.get("version").and_then(|version| version.as_u64()) {
+                Some(version) => Ok(ClientMessage::Hello(
+                    version as u32,
+                    message
+                        .get("features")
+                        .and_then(|features| features.as_array())
+                        .map(|features| {
+                            features
+                                .iter()
+                                .filter_map(|feature| feature.as_str())
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                )),
+                None => Err(String::from("hello needs a version")),
+            },
+            Some("name") => match field("name") {
+                Some(name) => Ok(ClientMessage::Name(String::from(name))),
+                None => Err(String::from("name needs a name")),
+            },
+            Some("register") => match (field("name"), field("password")) {
+                (Some(name), Some(password)) => Ok(ClientMessage::Register(
+                    String::from(name),
+                    String::from(password),
+                )),
+                _ => Err(String::from("register needs a name and password")),
+            },
+            Some("login") => match (field("name"), field("password")) {
+                (Some(name), Some(password)) => Ok(ClientMessage::Login(
+                    String::from(name),
+                    String::from(password),
+                )),
+                _ => Err(String::from("login needs a name and password")),
+            },
+            Some("authenticate") => match field("token") {
+                Some(token) => Ok(ClientMessage::Authenticate(String::from(token))),
+                None => Err(String::from("authenticate needs a token")),
+            },
+            Some("resume") => match field("session") {
+                Some(session) => Ok(ClientMessage::Resume(String::from(session))),
+                None => Err(String::from("resume needs a session")),
+            },
+            Some("list") => Ok(ClientMessage::List),
+            Some("create") => {
+                let mut options = TableOptions::standard();
+                if let Some(rules) = field("rules") {
+                    options.rules = String::from(rules);
+                }
+                if let Some(stakes) = message.get("stakes") {
+                    match stakes.as_u64() {
+                        Some(stakes) => options.stakes = stakes as u32,
+                        None => return Err(format!("invalid stakes: {}", stakes)),
+                    }
+                }
+                if let Some(private) = message.get("private") {
+                    options.private = private.as_bool() == Some(true);
+                }
+                Ok(ClientMessage::Create(options))
+            }
+            Some("join") => match message.get("table") {
+                None | Some(Value::Null) => Ok(ClientMessage::Join(None)),
+                Some(table) => match table.as_u64() {
+                    Some(table) => Ok(ClientMessage::Join(Some(table as u32))),
+                    None => Err(format!("invalid table: {}", table)),
+                },
+            },
+            Some("invite") => match field("code") {
+                Some(code) => Ok(ClientMessage::Invite(String::from(code))),
+                None => Err(String::from("invite needs a code")),
+            },
+            Some("match") => Ok(ClientMessage::Match(String::from(
+                field("rules").unwrap_or("Standard"),
+            ))),
+            Some("watch") => match message.get("table").and_then(|table| table.as_u64()) {
+                Some(table) => Ok(ClientMessage::Watch(table as u32)),
+                None => Err(String::from("watch needs a table")),
+            },
+            Some("challenge") => match field("opponent") {
+                Some(opponent) => Ok(ClientMessage::Challenge(
+                    String::from(opponent),
+                    String::from(field("rules").unwrap_or("Standard")),
+                    match message.get("hours") {
+                        None => CHALLENGE_HOURS,
+                        Some(hours) => match hours.as_u64() {
+                            Some(hours) => check_hours(hours)?,
+                            None => return Err(format!("invalid hours: {}", hours)),
+                        },
+                    },
+                )),
+                None => Err(String::from("challenge needs an opponent")),
+            },
+            Some("accept") => match message.get("game").and_then(|game| game.as_u64()) {
+                Some(game) => Ok(ClientMessage::Accept(game)),
+                None => Err(String::from("accept needs a game")),
+            },
+            Some("games") => Ok(ClientMessage::Games),
+            Some("show") => match message.get("game").and_then(|game| game.as_u64()) {
+                Some(game) => Ok(ClientMessage::Show(game)),
+                None => Err(String::from("show needs a game")),
+            },
+            Some("post") => match message.get("game").and_then(|game| game.as_u64()) {
+                Some(game) => Ok(ClientMessage::Post(game, json_action(&message)?)),
+                None => Err(String::from("post needs a game")),
+            },
+            Some("chat") => match field("text") {
+                Some(text) => Ok(ClientMessage::Chat(String::from(text))),
+                None => Err(String::from("chat needs some text")),
+            },
+            Some("emote") => match field("emote") {
+                Some(emote) => Ok(ClientMessage::Emote(String::from(emote))),
+                None => Err(String::from("emote needs an emote")),
+            },
+            Some("mute") => Ok(ClientMessage::Mute(true)),
+            Some("unmute") => Ok(ClientMessage::Mute(false)),
+            Some("play") => Ok(ClientMessage::Play(json_action(&message)?)),
+            _ => Err(format!("unknown message: {}", text)),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn to_line(&self) -> String {
+        match self {
+            ServerMessage::Welcome(version, features) => {
+                format!("welcome\t{}\t{}", version, features.join(" "))
+            }
+            ServerMessage::Account(name, token) => format!("account\t{}\t{}", name, token),
+            ServerMessage::Info(text) => format!("info\t{}", text),
+            ServerMessage::Error(text) => format!("error\t{}", text),
+            ServerMessage::Table(table) => format!("table\t{}", table),
+            ServerMessage::Session(session) => format!("session\t{}", session),
+            ServerMessage::Invite(code) => format!("invite\t{}", code),
+            // four fields for each table
+            ServerMessage::Tables(tables) => {
+                let mut line = String::from("tables");
+                for table in tables.iter() {
+                    line.push_str(&format!(
+                        "\t{}\t{}\t{}\t{}",
+                        table.table, table.host, table.rules, table.stakes
+                    ));
+                }
+                line
+            }
+            // four fields for each game
+            ServerMessage::Games(games) => {
+                let mut line = String::from("games");
+                for game in games.iter() {
+                    line.push_str(&format!(
+                        "\t{}\t{}\t{}\t{}",
+                        game.game, game.opponent, game.turn, game.deadline
+                    ));
+                }
+                line
+            }
+            ServerMessage::View(view) => format!("view\t{}", view_fields(view)),
+            ServerMessage::Watched(view) => format!("watched\t{}", view_fields(view)),
+            ServerMessage::Moved(player, action) => {
+                format!("moved\t{}\t{}", player, action_to_log(action))
+            }
+            ServerMessage::Chat(player, text) => format!("chat\t{}\t{}", player, text),
+            ServerMessage::Emote(player, emote) => format!("emote\t{}\t{}", player, emote),
+            ServerMessage::Result(winner, points) => format!("result\t{}\t{}", winner, points),
+        }
+    }
+
+    pub fn from_line(line: &str) -> Result<ServerMessage, String> {
+        let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+        match fields.as_slice() {
+            ["welcome", version, features] => match version.parse() {
+                Ok(version) => Ok(ServerMessage::Welcome(
+                    version,
+                    features.split_whitespace().map(String::from).collect(),
+                )),
+                Err(_) => Err(format!("invalid version: {}", version)),
+            },
+            ["account", name, token] => Ok(ServerMessage::Account(
+                String::from(*name),
+                String::from(*token),
+            )),
+            ["info", text] => Ok(ServerMessage::Info(String::from(*text))),
+            ["error", text] => Ok(ServerMessage::Error(String::from(*text))),
+            ["table", table] => match table.parse() {
+                Ok(table) => Ok(ServerMessage::Table(table)),
+                Err(_) => Err(format!("invalid table: {}", table)),
+            },
+            ["session", session] => Ok(ServerMessage::Session(String::from(*session))),
+            ["invite", code] => Ok(ServerMessage::Invite(String::from(*code))),
+            ["tables", tables @ ..] if tables.len() % 4 == 0 => Ok(ServerMessage::Tables(
+                tables
+                    .chunks(4)
+                    .map(parse_table)
+                    .collect::<Result<_, _>>()?,
+            )),
+            ["games", games @ ..] if games.len() % 4 == 0 => Ok(ServerMessage::Games(
+                games.chunks(4).map(parse_game).collect::<Result<_, _>>()?,
+            )),
+            ["view", view @ ..] if view.len() == 12 => Ok(ServerMessage::View(parse_view(view)?)),
+            ["watched", view @ ..] if view.len() == 12 => {
+                Ok(ServerMessage::Watched(parse_view(view)?))
+            }
+            ["moved", player, action] => Ok(ServerMessage::Moved(
+                String::from(*player),
+                action_from_log(action)?,
+            )),
+            ["chat", player, text] => Ok(ServerMessage::Chat(
+                String::from(*player),
+                String::from(*text),
+            )),
+            ["emote", player, emote] => Ok(ServerMessage::Emote(
+                String::from(*player),
+                String::from(*emote),
+            )),
+            ["result", winner, points] => match points.parse() {
+                Ok(points) => Ok(ServerMessage::Result(String::from(*winner), points)),
+                Err(_) => Err(format!("invalid number in: {}", line)),
+            },
+            _ => Err(format!("unknown message: {}", line)),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            ServerMessage::Welcome(version, features) => {
+                json!({"type": "welcome", "version": version, "features": features})
+            }
+            ServerMessage::Account(name, token) => {
+                json!({"type": "account", "name": name, "token": token})
+            }
+            ServerMessage::Info(text) => json!({"type": "info", "text": text}),
+            ServerMessage::Error(text) => json!({"type": "error", "text": text}),
+            ServerMessage::Table(table) => json!({"type": "table", "table": table}),
+            ServerMessage::Session(session) => json!({"type": "session", "session": session}),
+            ServerMessage::Invite(code) => json!({"type": "invite", "code": code}),
+            ServerMessage::Tables(tables) => json!({
+                "type": "tables",
+                "tables": tables
+                    .iter()
+                    .map(|table| json!({
+                        "table": table.table,
+                        "host": table.host,
+                        "rules": table.rules,
+                        "stakes": table.stakes,
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+            ServerMessage::Games(games) => json!({
+                "type": "games",
+                "games": games
+                    .iter()
+                    .map(|game| {
+                        let ongoing = !game.turn.is_empty();
+                        json!({
+                            "game": game.game,
+                            "opponent": game.opponent,
+                            "turn": if ongoing { Some(&game.turn) } else { None },
+                            "deadline": if ongoing && game.deadline > 0 {
+                                Some(game.deadline)
+                            } else {
+                                None
+                            },
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            }),
+            ServerMessage::View(view) => view_json("state", view),
+            ServerMessage::Watched(view) => view_json("watched", view),
+            ServerMessage::Moved(player, action) => {
+                let mut event = action_json(action);
+                event["type"] = json!("event");
+                event["player"] = json!(player);
+                event
+            }
+            ServerMessage::Chat(player, text) => {
+                json!({"type": "chat", "player": player, "text": text})
+            }
+            ServerMessage::Emote(player, emote) => {
+                json!({"type": "emote", "player": player, "emote": emote})
+            }
+            ServerMessage::Result(winner, points) => json!({
+                "type": "result",
+                "winner": if winner.is_empty() { None } else { Some(winner) },
+                "points": points,
+            }),
+        }
+    }
+}
+
+// the fields of one game in a games message
+fn parse_game(fields: &[&str]) -> Result<GameSummary, String> {
+    let number = |value: &str| match value.parse::<u64>() {
+        Ok(number) => Ok(number),
+        Err(_) => Err(format!("invalid number: {}", value)),
+    };
+    Ok(GameSummary {
+        game: number(fields[0])?,
+        opponent: String::from(fields[1]),
+        turn: String::from(fields[2]),
+        deadline: number(fields[3])?,
+    })
+}
+
+// the action of a play or post message
+pub fn json_action(message: &Value) -> Result<Action, String> {
+    let field = |name: &str| message.get(name).and_then(|value| value.as_str());
+    let action = match (field("action"), field("card")) {
+        (Some(action), Some(card)) => format!("{} {}", action, card),
+        (Some(action), None) => String::from(action),
+        _ => return Err(String::from("a move needs an action")),
+    };
+    action_from_log(&action)
+}
+
+// an action as the action and card fields of play and event messages
+pub fn action_json(action: &Action) -> Value {
+    let log = action_to_log(action);
+    let mut parts = log.split(' ');
+    let mut fields = json!({"action": parts.next()});
+    if let Some(card) = parts.next() {
+        fields["card"] = json!(card);
+    }
+    fields
+}
+
+// a view's fields, tab separated
+fn view_fields(view: &PlayerView) -> String {
+    let fields = [
+        view.player.clone(),
+        view.opponent.clone(),
+        view.turn.clone(),
+        String::from(phase_name(view.phase)),
+        codes(&view.hand),
+        meld_codes(&view.melds),
+        meld_codes(&view.opponent_melds),
+        view.opponent_cards.to_string(),
+        match &view.opponent_deadwood {
+            Some(deadwood) => codes(deadwood),
+            None => String::from("-"),
+        },
+        codes(&view.discard_pile),
+        view.stock.to_string(),
+        codes(&view.opponent_pickups),
+    ];
+    fields.join("\t")
+}
+
+fn view_json(kind: &str, view: &PlayerView) -> Value {
+    json!({
+        "type": kind,
+        "player": view.player,
+        "opponent": view.opponent,
+        "turn": view.turn,
+        "phase": phase_name(view.phase),
+        "hand": code_list(&view.hand),
+        "melds": view.melds.iter().map(|meld| code_list(meld)).collect::<Vec<_>>(),
+        "opponent_melds": view
+            .opponent_melds
+            .iter()
+            .map(|meld| code_list(meld))
+            .collect::<Vec<_>>(),
+        "opponent_cards": view.opponent_cards,
+        "opponent_deadwood": view.opponent_deadwood.as_ref().map(|deadwood| code_list(deadwood)),
+        "discard_pile": code_list(&view.discard_pile),
+        "stock": view.stock,
+        "opponent_pickups": code_list(&view.opponent_pickups),
+    })
+}
+
+// whether a player can go by the name. Names end up in lines of the protocol, in saved hands
+// and in the accounts file, so tabs, newlines and other control characters are refused.
+pub fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.chars().count() <= MAX_NAME_LENGTH
+        && !name.chars().any(char::is_control)
+}
+
+// the hours a challenge gives for each move, which must be at least one and at most thirty days
+fn check_hours(hours: u64) -> Result<u64, String> {
+    if (1..=MAX_CHALLENGE_HOURS).contains(&hours) {
+        Ok(hours)
+    } else {
+        Err(format!(
+            "hours must be from 1 to {}, not {}",
+            MAX_CHALLENGE_HOURS, hours
+        ))
+    }
+}
+
+// the fields of one table in a tables message
+fn parse_table(fields: &[&str]) -> Result<OpenTable, String> {
+    let number = |value: &str| match value.parse::<u32>() {
+        Ok(number) => Ok(number),
+        Err(_) => Err(format!("invalid number: {}", value)),
+    };
+    Ok(OpenTable {
+        table: number(fields[0])?,
+        host: String::from(fields[1]),
+        rules: String::from(fields[2]),
+        stakes: number(fields[3])?,
+    })
+}
+
+// the fields of a view message in the order to_line writes them
+fn parse_view(fields: &[&str]) -> Result<PlayerView, String> {
+    let number = |value: &str| match value.parse::<usize>() {
+        Ok(number) => Ok(number),
+        Err(_) => Err(format!("invalid number: {}", value)),
+    };
+    Ok(PlayerView {
+        player: String::from(fields[0]),
+        opponent: String::from(fields[1]),
+        turn: String::from(fields[2]),
+        phase: parse_phase(fields[3])?,
+        hand: parse_cards(fields[4])?,
+        order: HandOrder::Arrival,
+        melds: parse_melds(fields[5])?,
+        opponent_melds: parse_melds(fields[6])?,
+        opponent_cards: number(fields[7])?,
+        opponent_deadwood: match fields[8] {
+            "-" => None,
+            deadwood => Some(parse_cards(deadwood)?),
+        },
+        discard_pile: parse_cards(fields[9])?,
+        stock: number(fields[10])?,
+        opponent_pickups: parse_cards(fields[11])?,
+    })
+}
+
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Draw => "draw",
+        Phase::Discard => "discard",
+        Phase::Showdown => "showdown",
+        Phase::Over => "over",
+    }
+}
+
+fn parse_phase(name: &str) -> Result<Phase, String> {
+    match name {
+        "draw" => Ok(Phase::Draw),
+        "discard" => Ok(Phase::Discard),
+        "showdown" => Ok(Phase::Showdown),
+        "over" => Ok(Phase::Over),
+        _ => Err(format!("unknown phase: {}", name)),
+    }
+}
+
+fn code_list(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|card| card.code()).collect()
+}
+
+fn codes(cards: &[Card]) -> String {
+    let codes: Vec<String> = cards.iter().map(|card| card.code()).collect();
+    codes.join(" ")
+}
+
+// melds separated by commas
+fn meld_codes(melds: &[Vec<Card>]) -> String {
+    let melds: Vec<String> = melds.iter().map(|meld| codes(meld)).collect();
+    melds.join(",")
+}
+
+fn parse_card(code: &str) -> Result<Card, String> {
+    match Card::from_code(code) {
+        Some(card) => Ok(card),
+        None => Err(format!("invalid card: {}", code)),
+    }
+}
+
+fn parse_cards(codes: &str) -> Result<Vec<Card>, String> {
+    codes.split_whitespace().map(parse_card).collect()
+}
+
+fn parse_melds(codes: &str) -> Result<Vec<Vec<Card>>, String> {
+    codes
+        .split(',')
+        .filter(|meld| !meld.trim().is_empty())
+        .map(parse_cards)
+        .collect()
+}
@@ -0,0 +1,264 @@
+// Glicko-2 ratings from finished matches, kept separately for each rule set. Every match is
+// its own rating period, so ratings move after each result rather than in batches.
+// See http://www.glicko.net/glicko/glicko2.pdf for the algorithm.
+//
+// Ratings follow the seat name, so anything that plays a seat, person or bot, is rated on the
+// same scale. A name with an account is only rated on the hands its account logged in to.
+
+use std::f64::consts::PI;
+
+use crate::record::{format_date, HandRecord};
+use crate::stats::{match_results, MatchResult};
+
+// converts between the Glicko scale and the Glicko-2 scale
+const SCALE: f64 = 173.7178;
+// how much volatility can change between periods
+const TAU: f64 = 0.5;
+const CONVERGENCE: f64 = 0.000001;
+
+// gin leaderboard [rules]
+pub fn run_leaderboard(args: &[String]) {
+    let rules = match args.first() {
+        Some(rules) => rules.as_str(),
+        None => "Standard",
+    };
+    let ladder = Ladder::from_results(&match_results(&HandRecord::load_all()));
+    let leaderboard = ladder.leaderboard(rules);
+    if leaderboard.is_empty() {
+        println!("no finished {} matches yet", rules);
+        return;
+    }
+    println!("{} leaderboard", rules);
+    for (index, player) in leaderboard.iter().enumerate() {
+        println!(
+            "{}. {} {:.0} (±{:.0}) {} matches",
+            index + 1,
+            player.name,
+            player.rating.rating,
+            player.rating.deviation,
+            player.matches
+        );
+    }
+}
+
+// gin rating <player> [rules]
+pub fn run_history(args: &[String]) {
+    let name = match args.first() {
+        Some(name) => name,
+        None => {
+            println!("usage: gin rating <player> [rules]");
+            return;
+        }
+    };
+    let rules = match args.get(1) {
+        Some(rules) => rules.as_str(),
+        None => "Standard",
+    };
+    let ladder = Ladder::from_results(&match_results(&HandRecord::load_all()));
+    match ladder.player(name, rules) {
+        Some(player) => {
+            println!("{} ({})", player.name, player.rules);
+            for (played, rating, deviation) in player.history.iter() {
+                println!("{} {:.0} (±{:.0})", format_date(*played), rating, deviation);
+            }
+        }
+        None => println!("{} has no finished {} matches", name, rules),
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Rating {
+    pub fn new() -> Self {
+        Rating {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+
+    // the rating after one game against `opponent`; score is 1 for a win and 0 for a loss
+    pub fn update(&self, opponent: &Rating, score: f64) -> Rating {
+        self.update_period(&[(*opponent, score)])
+    }
+
+    // the rating after a rating period of games, each an opponent and the score against them
+    pub fn update_period(&self, games: &[(Rating, f64)]) -> Rating {
+        let mu = (self.rating - 1500.0) / SCALE;
+        let phi = self.deviation / SCALE;
+
+        // each game's g and expected score, steps 3 and 4 of the paper
+        let games: Vec<(f64, f64, f64)> = games
+            .iter()
+            .map(|(opponent, score)| {
+                let opponent_mu = (opponent.rating - 1500.0) / SCALE;
+                let opponent_phi = opponent.deviation / SCALE;
+                let g = 1.0 / (1.0 + 3.0 * opponent_phi.powi(2) / PI.powi(2)).sqrt();
+                let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+                (g, expected, *score)
+            })
+            .collect();
+        let variance = 1.0
+            / games
+                .iter()
+                .map(|(g, expected, _)| g.powi(2) * expected * (1.0 - expected))
+                .sum::<f64>();
+        let improvement: f64 = games
+            .iter()
+            .map(|(g, expected, score)| g * (score - expected))
+            .sum();
+        let delta = variance * improvement;
+
+        let volatility = self.new_volatility(phi, variance, delta);
+        let pre_period_phi = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / pre_period_phi.powi(2) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi.powi(2) * improvement;
+
+        Rating {
+            rating: SCALE * new_mu + 1500.0,
+            deviation: SCALE * new_phi,
+            volatility,
+        }
+    }
+
+    // step 5 of the paper, using the Illinois algorithm
+    fn new_volatility(&self, phi: f64, variance: f64, delta: f64) -> f64 {
+        let a = self.volatility.powi(2).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta.powi(2) - phi.powi(2) - variance - ex)
+                / (2.0 * (phi.powi(2) + variance + ex).powi(2))
+                - (x - a) / TAU.powi(2)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta.powi(2) > phi.powi(2) + variance {
+            (delta.powi(2) - phi.powi(2) - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+
+        let mut f_lower = f(lower);
+        let mut f_upper = f(upper);
+        while (upper - lower).abs() > CONVERGENCE {
+            let next = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_next = f(next);
+            if f_next * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = next;
+            f_upper = f_next;
+        }
+        (lower / 2.0).exp()
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating::new()
+    }
+}
+
+pub struct PlayerRating {
+    pub name: String,
+    pub rules: String,
+    pub rating: Rating,
+    pub matches: u32,
+    // (when, rating, deviation) after each match
+    pub history: Vec<(u64, f64, f64)>,
+}
+
+pub struct Ladder {
+    pub players: Vec<PlayerRating>,
+}
+
+impl Ladder {
+    // results should be in the order the matches were decided, as match_results returns them
+    pub fn from_results(results: &[MatchResult]) -> Self {
+        let mut ladder = Ladder {
+            players: Vec::new(),
+        };
+        for result in results.iter() {
+            let winner = match &result.winner {
+                Some(winner) => winner,
+                None => continue,
+            };
+            let first = ladder.index(&result.first_player, &result.rules);
+            let second = ladder.index(&result.second_player, &result.rules);
+            let first_rating = ladder.players[first].rating;
+            let second_rating = ladder.players[second].rating;
+            let first_score = if *winner == result.first_player {
+                1.0
+            } else {
+                0.0
+            };
+
+            ladder.players[first].record(
+                first_rating.update(&second_rating, first_score),
+                result.played,
+            );
+            ladder.players[second].record(
+                second_rating.update(&first_rating, 1.0 - first_score),
+                result.played,
+            );
+        }
+        ladder
+    }
+
+    pub fn player(&self, name: &str, rules: &str) -> Option<&PlayerRating> {
+        self.players
+            .iter()
+            .find(|player| player.name == name && player.rules == rules)
+    }
+
+    // highest rated first
+    pub fn leaderboard(&self, rules: &str) -> Vec<&PlayerRating> {
+        let mut leaderboard: Vec<&PlayerRating> = self
+            .players
+            .iter()
+            .filter(|player| player.rules == rules)
+            .collect();
+        leaderboard.sort_by(|a, b| b.rating.rating.total_cmp(&a.rating.rating));
+        leaderboard
+    }
+
+    fn index(&mut self, name: &str, rules: &str) -> usize {
+        match self
+            .players
+            .iter()
+            .position(|player| player.name == name && player.rules == rules)
+        {
+            Some(index) => index,
+            None => {
+                self.players.push(PlayerRating {
+                    name: String::from(name),
+                    rules: String::from(rules),
+                    rating: Rating::new(),
+                    matches: 0,
+                    history: Vec::new(),
+                });
+                self.players.len() - 1
+            }
+        }
+    }
+}
+
+impl PlayerRating {
+    fn record(&mut self, rating: Rating, played: u64) {
+        self.rating = rating;
+        self.matches += 1;
+        self.history.push((played, rating.rating, rating.deviation));
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cards::CardStyle;
+use crate::{notation, Action, Card, Deck, GinGame, Move};
+
+const HANDS_DIR: &str = "hands";
+
+static LAST_MATCH_ID: AtomicU64 = AtomicU64::new(0);
+
+// an id for a new match, which names its saved hands: the time in milliseconds, kept above the
+// last id given out so that tables starting together never write over each other's hands
+pub fn new_match_id() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let last = LAST_MATCH_ID
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap();
+    now.max(last + 1)
+}
+
+pub struct HandRecord {
+    pub match_id: u64,
+    pub first_player: String,
+    pub second_player: String,
+    // seconds since the unix epoch
+    pub played: u64,
+    pub seed: u64,
+    pub rules: String,
+    pub first_turn: String,
+    // the players who had logged in to their accounts, on a server with accounts
+    pub accounts: Vec<String>,
+    // deck order after the final shuffle, top of the stock last
+    pub deck: Vec<Card>,
+    pub moves: Vec<Move>,
+    // what was said at the table, in the order it was said
+    pub chat: Vec<ChatLine>,
+    pub winner: String,
+    pub points: i32,
+}
+
+// something said at the table during a hand
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatLine {
+    // how many moves had been made when it was said
+    pub after: usize,
+    pub player: String,
+    pub text: String,
+    // one of protocol::EMOTES rather than typed text
+    pub emote: bool,
+}
+
+impl ChatLine {
+    fn to_log(&self) -> String {
+        let key = if self.emote { "emote" } else { "chat" };
+        format!("{} {}: {}\n", key, self.player, self.text)
+    }
+}
+
+impl HandRecord {
+    pub fn save(&self, hand_number: usize) {
+        if let Err(e) = fs::create_dir_all(HANDS_DIR) {
+            println!("could not save hand: {}", e);
+            return;
+        }
+        let path = Path::new(HANDS_DIR).join(format!("{}-{}.log", self.match_id, hand_number));
+        match fs::write(&path, self.to_log()) {
+            Ok(_) => println!("hand saved to {}", path.display()),
+            Err(e) => println!("could not save hand: {}", e),
+        }
+    }
+
+    // reads either a saved hand log or a hand written in gin notation
+    pub fn load(path: &str) -> Result<HandRecord, String> {
+        let log =
+            fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        if log.trim_start().starts_with('[') {
+            notation::parse(&log)
+        } else {
+            HandRecord::from_log(&log)
+        }
+    }
+
+    // every saved hand, oldest first
+    pub fn load_all() -> Vec<HandRecord> {
+        HandRecord::load_dir(HANDS_DIR)
+    }
+
+    // every hand in the directory, oldest first
+    pub fn load_dir(dir: &str) -> Vec<HandRecord> {
+        let mut records = Vec::new();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return records,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match HandRecord::load(&path.to_string_lossy()) {
+                Ok(record) => records.push(record),
+                Err(e) => println!("skipping {}: {}", path.display(), e),
+            }
+        }
+        records.sort_by_key(|record| (record.played, record.match_id));
+        records
+    }
+
+    // whether the named seat was that player. Once a name has logged in to an account, hands
+    // played under it without logging in are someone else's and do not count for it.
+    pub fn played_as(&self, name: &str, accounts: &HashSet<String>) -> bool {
+        !accounts.contains(name) || self.accounts.iter().any(|account| account == name)
+    }
+
+    // the deck as it was before the deal
+    pub fn deal(&self) -> Deck {
+        Deck {
+            cards: self.deck.clone(),
+        }
+    }
+
+    // deals the recorded deck and plays the first `step` moves on top of it
+    pub fn play_to(&self, step: usize) -> Result<GinGame, String> {
+        let mut game = GinGame::new(self.first_player.clone(), self.second_player.clone());
+        game.deck = self.deal();
+        game.current_turn = self.first_turn.clone();
+        game.deal();
+        for (index, player_move) in self.moves.iter().take(step).enumerate() {
+            if let Err(e) = check_move(&game, player_move) {
+                return Err(format!("move {}: {}", index + 1, e));
+            }
+            game.apply_move(player_move.clone());
+        }
+        Ok(game)
+    }
+
+    pub fn to_log(&self) -> String {
+        let mut log = String::new();
+        log.push_str(&format!("match {}\n", self.match_id));
+        log.push_str(&format!("first_player {}\n", self.first_player));
+        log.push_str(&format!("second_player {}\n", self.second_player));
+        log.push_str(&format!("played {}\n", self.played));
+        log.push_str(&format!("seed {}\n", self.seed));
+        log.push_str(&format!("rules {}\n", self.rules));
+        log.push_str(&format!("first_turn {}\n", self.first_turn));
+        for account in self.accounts.iter() {
+            log.push_str(&format!("account {}\n", account));
+        }
+        let deck: Vec<String> = self.deck.iter().map(|card| card.code()).collect();
+        log.push_str(&format!("deck {}\n", deck.join(" ")));
+        // chat goes in among the moves, where it was said
+        let mut chat = self.chat.iter().peekable();
+        for (index, player_move) in self.moves.iter().enumerate() {
+            while let Some(line) = chat.next_if(|line| line.after <= index) {
+                log.push_str(&line.to_log());
+            }
+            log.push_str(&format!(
+                "move {}: {}\n",
+                player_move.player,
+                action_to_log(&player_move.action)
+            ));
+        }
+        for line in chat {
+            log.push_str(&line.to_log());
+        }
+        if !self.winner.is_empty() {
+            log.push_str(&format!("result {} {}\n", self.points, self.winner));
+        }
+        log
+    }
+
+    pub fn from_log(log: &str) -> Result<HandRecord, String> {
+        let mut record = HandRecord::empty();
+
+        for line in log.lines() {
+            let (key, value) = match line.split_once(' ') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            match key {
+                "match" => record.match_id = parse_number(value)?,
+                "first_player" => record.first_player = String::from(value),
+                "second_player" => record.second_player = String::from(value),
+                "played" => record.played = parse_number(value)?,
+                "seed" => record.seed = parse_number(value)?,
+                "rules" => record.rules = String::from(value),
+                "first_turn" => record.first_turn = String::from(value),
+                "account" => record.accounts.push(String::from(value)),
+                "deck" => {
+                    for code in value.split_whitespace() {
+                        match Card::from_code(code) {
+                            Some(card) => record.deck.push(card),
+                            None => return Err(format!("unknown card {}", code)),
+                        }
+                    }
+                }
+                "move" => {
+                    let (player, action) = match value.rsplit_once(": ") {
+                        Some(pair) => pair,
+                        None => return Err(format!("invalid move: {}", value)),
+                    };
+                    record.moves.push(Move {
+                        player: String::from(player),
+                        action: action_from_log(action)?,
+                    });
+                }
+                "chat" | "emote" => {
+                    let (player, text) = match value.split_once(": ") {
+                        Some(pair) => pair,
+                        None => return Err(format!("invalid {}: {}", key, value)),
+                    };
+                    record.chat.push(ChatLine {
+                        after: record.moves.len(),
+                        player: String::from(player),
+                        text: String::from(text),
+                        emote: key == "emote",
+                    });
+                }
+                "result" => {
+                    let (points, winner) = match value.split_once(' ') {
+                        Some(pair) => pair,
+                        None => return Err(format!("invalid result: {}", value)),
+                    };
+                    record.points = parse_number(points)?;
+                    record.winner = String::from(winner);
+                }
+                _ => return Err(format!("unknown entry: {}", line)),
+            }
+        }
+
+        record.check()?;
+        Ok(record)
+    }
+
+    pub fn empty() -> HandRecord {
+        HandRecord {
+            match_id: 0,
+            first_player: String::from(""),
+            second_player: String::from(""),
+            played: 0,
+            seed: 0,
+            rules: String::from(""),
+            first_turn: String::from(""),
+            accounts: Vec::new(),
+            deck: Vec::new(),
+            moves: Vec::new(),
+            chat: Vec::new(),
+            winner: String::from(""),
+            points: 0,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.first_player.is_empty() || self.second_player.is_empty() {
+            return Err(String::from("hand is missing its players"));
+        }
+        if self.first_turn != self.first_player && self.first_turn != self.second_player {
+            return Err(String::from("hand is missing who played first"));
+        }
+        if self
+            .accounts
+            .iter()
+            .any(|account| *account != self.first_player && *account != self.second_player)
+        {
+            return Err(String::from(
+                "hand has an account for someone who did not play",
+            ));
+        }
+        Deck::check_pack(&self.deck)
+    }
+}
+
+// a saved hand may have been edited by hand, so make sure every move can be played
+fn check_move(game: &GinGame, player_move: &Move) -> Result<(), String> {
+    let (player, opponent) = if player_move.player == game.first_player.name {
+        (&game.first_player, &game.second_player)
+    } else if player_move.player == game.second_player.name {
+        (&game.second_player, &game.first_player)
+    } else {
+        return Err(format!("unknown player {}", player_move.player));
+    };
+    let (card, melds, meld_index) = match &player_move.action {
+        Action::DrawStock if game.deck.cards.is_empty() => {
+            return Err(String::from("the stock is empty"))
+        }
+        Action::DrawDiscard if game.discard_pile.cards.is_empty() => {
+            return Err(String::from("the discard pile is empty"))
+        }
+        Action::Discard(card) => (card, &player.melds, None),
+        Action::Meld(card, meld_index) => (card, &player.melds, Some(*meld_index)),
+        Action::LayOff(card, meld_index) => (card, &opponent.melds, Some(*meld_index)),
+        _ => return Ok(()),
+    };
+    if !player.hand.contains(card) {
+        return Err(format!(
+            "{} does not hold the {}",
+            player.name,
+            card.show(CardStyle::current())
+        ));
+    }
+    if let Some(meld_index) = meld_index {
+        if meld_index >= melds.collection.len() {
+            return Err(format!("there is no meld {}", meld_index));
+        }
+    }
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number: {}", value))
+}
+
+// "YYYY.MM.DD" for seconds since the unix epoch
+pub fn format_date(secs: u64) -> String {
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+// every name that logged in to an account in any of the records
+pub fn account_names(records: &[HandRecord]) -> HashSet<String> {
+    records
+        .iter()
+        .flat_map(|record| record.accounts.iter().cloned())
+        .collect()
+}
+
+// midnight at the start of a "YYYY.MM.DD" date, in seconds since the unix epoch
+pub fn parse_date(date: &str) -> Option<u64> {
+    let parts: Vec<i64> = date
+        .split(['.', '-'])
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<i64>>>()?;
+    if parts.len() != 3 || !(1..=12).contains(&parts[1]) || !(1..=31).contains(&parts[2]) {
+        return None;
+    }
+    let (month, day) = (parts[1], parts[2]);
+    let year = if month <= 2 { parts[0] - 1 } else { parts[0] };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    if days < 0 {
+        return None;
+    }
+    Some(days as u64 * 86400)
+}
+
+pub fn action_to_log(action: &Action) -> String {
+    match action {
+        Action::DrawStock => String::from("draw-stock"),
+        Action::DrawDiscard => String::from("draw-discard"),
+        Action::Knock => String::from("knock"),
+        Action::Gin => String::from("gin"),
+        Action::Discard(card) => format!("discard {}", card.code()),
+        Action::NewMeld => String::from("new-meld"),
+        Action::Meld(card, meld_index) => format!("meld {} {}", card.code(), meld_index),
+        Action::LayOff(card, meld_index) => format!("lay-off {} {}", card.code(), meld_index),
+    }
+}
+
+pub fn action_from_log(entry: &str) -> Result<Action, String> {
+    let parts: Vec<&str> = entry.split_whitespace().collect();
+    let card = |index: usize| match parts.get(index).and_then(|code| Card::from_code(code)) {
+        Some(card) => Ok(card),
+        None => Err(format!("invalid card in: {}", entry)),
+    };
+    let meld_index = |index: usize| match parts.get(index).and_then(|n| n.parse().ok()) {
+        Some(meld_index) => Ok(meld_index),
+        None => Err(format!("invalid meld in: {}", entry)),
+    };
+
+    match parts.first().copied() {
+        Some("draw-stock") => Ok(Action::DrawStock),
+        Some("draw-discard") => Ok(Action::DrawDiscard),
+        Some("knock") => Ok(Action::Knock),
+        Some("gin") => Ok(Action::Gin),
+        Some("discard") => Ok(Action::Discard(card(1)?)),
+        Some("new-meld") => Ok(Action::NewMeld),
+        Some("meld") => Ok(Action::Meld(card(1)?, meld_index(2)?)),
+        Some("lay-off") => Ok(Action::LayOff(card(1)?, meld_index(2)?)),
+        _ => Err(format!("unknown action: {}", entry)),
+    }
+}
@@ -0,0 +1,144 @@
+use std::io;
+
+use crate::cards::CardStyle;
+use crate::record::HandRecord;
+use crate::{Action, GinGame, Move, Player};
+
+// gin replay <file> [--as <player>]
+pub fn run(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("usage: gin replay <file> [--as <player>]");
+            return;
+        }
+    };
+    let perspective = match args.get(1).map(|arg| arg.as_str()) {
+        Some("--as") => match args.get(2) {
+            Some(name) => Some(name.as_str()),
+            None => {
+                println!("usage: gin replay <file> [--as <player>]");
+                return;
+            }
+        },
+        _ => None,
+    };
+
+    let record = match HandRecord::load(path) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if let Some(name) = perspective {
+        if name != record.first_player && name != record.second_player {
+            println!("{} did not play in this hand", name);
+            return;
+        }
+    }
+
+    if let Err(e) = record.play_to(record.moves.len()) {
+        println!("{}", e);
+        return;
+    }
+
+    let mut step = 0;
+    let mut input = String::new();
+    loop {
+        let game = record.play_to(step).unwrap();
+        display_step(&record, &game, step, perspective);
+
+        println!("next (n), previous (p), start (s), end (e) or quit (q)?");
+        input.clear();
+        io::stdin().read_line(&mut input).unwrap();
+        match input.trim() {
+            "n" | "" => {
+                if step < record.moves.len() {
+                    step += 1;
+                } else {
+                    println!("end of hand.");
+                }
+            }
+            "p" => {
+                if step > 0 {
+                    step -= 1;
+                } else {
+                    println!("start of hand.");
+                }
+            }
+            "s" => step = 0,
+            "e" => step = record.moves.len(),
+            "q" => break,
+            _ => println!("Invalid command."),
+        }
+    }
+}
+
+fn display_step(record: &HandRecord, game: &GinGame, step: usize, perspective: Option<&str>) {
+    println!("==========");
+    if step == 0 {
+        println!("Deal: {} to play first", record.first_turn);
+    } else {
+        println!(
+            "Move {} of {}: {}",
+            step,
+            record.moves.len(),
+            describe_move(&record.moves[step - 1], CardStyle::current())
+        );
+    }
+    // what was said at the table before the next move
+    for line in record.chat.iter().filter(|line| line.after == step) {
+        if line.emote {
+            println!("{} sends {}", line.player, line.text);
+        } else {
+            println!("{}: {}", line.player, line.text);
+        }
+    }
+    println!(" ");
+
+    if let Some(name) = perspective {
+        // only what that player could see at the time
+        game.view(name).display();
+        return;
+    }
+    for player in [&game.first_player, &game.second_player] {
+        player.display_player_hand();
+        display_player_melds(player);
+    }
+
+    game.display_discard_pile();
+    println!("Cards left in stock: {}", game.deck.cards.len());
+    println!(" ");
+}
+
+fn display_player_melds(player: &Player) {
+    if !player.melds.collection.is_empty() {
+        println!("{}'s melds: ", player.name);
+        player.melds.display_melds();
+        println!(" ");
+    }
+}
+
+// cards are shown in the style given, so the tui can leave the colour to its own drawing
+pub fn describe_move(player_move: &Move, style: CardStyle) -> String {
+    let description = match &player_move.action {
+        Action::DrawStock => String::from("draws from the stock"),
+        Action::DrawDiscard => String::from("draws from the discard pile"),
+        Action::Knock => String::from("knocks"),
+        Action::Gin => String::from("calls gin"),
+        Action::Discard(card) => format!("discards the {}", card.show(style)),
+        Action::NewMeld => String::from("starts a new meld"),
+        Action::Meld(card, meld_index) => {
+            format!("adds the {} to meld {}", card.show(style), meld_index)
+        }
+        Action::LayOff(card, meld_index) => {
+            format!(
+                "lays off the {} on opponent's meld {}",
+                card.show(style),
+                meld_index
+            )
+        }
+    };
+    format!("{} {}", player_move.player, description)
+}
@@ -0,0 +1,1504 @@
+// gin-server hosts matches between its clients. The server holds the only copy of each game:
+// clients send the moves they want to make, the server checks them against the rules and sends
+// each client just what its player can see. `gin connect` clients speak the line protocol and
+// browsers speak JSON over a WebSocket, and either can sit at the same table.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::mem;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, SendError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+use crate::accounts::{self, Accounts};
+use crate::cards::CardStyle;
+use crate::clock::{Clock, TimeControl, Timeout};
+use crate::correspondence::Correspondence;
+use crate::protocol::{
+    self, ClientMessage, OpenTable, ServerMessage, TableOptions, CHAT, EMOTES, OLDEST_VERSION,
+    PROTOCOL_VERSION,
+};
+use crate::rating::{Ladder, Rating};
+use crate::record::{new_match_id, ChatLine, HandRecord};
+use crate::stats::match_results;
+use crate::view::PlayerView;
+use crate::{api, websocket, Action, GinGame, Move, Phase, RuleSet};
+
+// how far apart two waiting players' ratings can be to be matched straight away
+const MATCH_RANGE: f64 = 100.0;
+// how much further apart they can be for every second they have waited
+const MATCH_WIDENING: f64 = 10.0;
+const MATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+// invite codes leave out letters that are easily mistaken for one another
+const INVITE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const INVITE_LENGTH: usize = 6;
+
+const SESSION_LENGTH: usize = 16;
+
+// how much a player can say at a table: messages at a time, how long until each is forgotten,
+// and how long a message can be
+const CHAT_LIMIT: usize = 5;
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+const CHAT_LENGTH: usize = 200;
+
+// how long a disconnected player's seat is held, and what becomes of it after that
+#[derive(Clone, Copy)]
+pub struct Reconnection {
+    pub timeout: Duration,
+    // a bot plays the seat from then on, rather than the match being forfeited
+    pub bot: bool,
+}
+
+impl Reconnection {
+    pub fn standard() -> Self {
+        Reconnection {
+            timeout: Duration::from_secs(60),
+            bot: false,
+        }
+    }
+}
+
+// how the server is set up
+pub struct ServerOptions {
+    pub reconnection: Reconnection,
+    // how far behind the play spectators see both hands, if they are shown at all
+    pub reveal: Option<Duration>,
+    // the directory correspondence games are kept in
+    pub games: String,
+    // where correspondence players are told it is their move, rather than the mail stub
+    pub webhook: Option<String>,
+    pub time_control: TimeControl,
+    // the file of player accounts; without one anyone can play under any name
+    pub accounts: Option<String>,
+}
+
+impl ServerOptions {
+    pub fn standard() -> Self {
+        ServerOptions {
+            reconnection: Reconnection::standard(),
+            reveal: None,
+            games: String::from("games"),
+            webhook: None,
+            time_control: TimeControl::untimed(),
+            accounts: None,
+        }
+    }
+}
+
+// gin-server [address] [--websocket <address>] [--api <address>] [--reconnect <seconds>] [--bot]
+// [--reveal <seconds>] [--games <dir>] [--webhook <url>] [--move-time <seconds>]
+// [--bank <seconds>] [--increment <seconds>] [--on-timeout <play|forfeit>] [--accounts <file>]
+pub fn run(
+    address: &str,
+    websocket_address: Option<&str>,
+    api_address: Option<&str>,
+    options: ServerOptions,
+) {
+    let lobby = Lobby::create(options);
+    let listener = match bind(address) {
+        Some(listener) => listener,
+        None => return,
+    };
+    if let Some(websocket_address) = websocket_address {
+        let websocket_listener = match bind(websocket_address) {
+            Some(listener) => listener,
+            None => return,
+        };
+        let lobby = lobby.clone();
+        thread::spawn(move || serve_websockets(websocket_listener, lobby));
+    }
+    if let Some(api_address) = api_address {
+        let api_listener = match bind(api_address) {
+            Some(listener) => listener,
+            None => return,
+        };
+        thread::spawn(move || api::serve(api_listener, String::from("hands")));
+    }
+    serve(listener, lobby);
+}
+
+fn bind(address: &str) -> Option<TcpListener> {
+    match TcpListener::bind(address) {
+        Ok(listener) => {
+            println!("listening on {}", address);
+            Some(listener)
+        }
+        Err(e) => {
+            println!("could not listen on {}: {}", address, e);
+            None
+        }
+    }
+}
+
+// accepts `gin connect` clients
+pub fn serve(listener: TcpListener, lobby: Arc<Mutex<Lobby>>) {
+    for stream in listener.incoming() {
+        match stream.and_then(line_connection) {
+            Ok(connection) => {
+                let lobby = lobby.clone();
+                thread::spawn(move || welcome(connection, lobby));
+            }
+            Err(e) => println!("connection failed: {}", e),
+        }
+    }
+}
+
+// accepts browser clients
+pub fn serve_websockets(listener: TcpListener, lobby: Arc<Mutex<Lobby>>) {
+    for stream in listener.incoming() {
+        let lobby = lobby.clone();
+        // the WebSocket handshake happens on the new thread so a slow client holds up nobody
+        thread::spawn(move || {
+            match stream
+                .map_err(|e| e.to_string())
+                .and_then(websocket::connection)
+            {
+                Ok(connection) => welcome(connection, lobby),
+                Err(e) => println!("connection failed: {}", e),
+            }
+        });
+    }
+}
+
+// one client, whichever protocol it speaks
+pub struct Connection {
+    pub sender: Sender<ServerMessage>,
+    // None once the client disconnects
+    pub receiver: Receiver<Option<Result<ClientMessage, String>>>,
+    // what the client agreed to in the handshake
+    pub features: Vec<String>,
+    // the account the client logged in as, on a server with accounts
+    pub account: Option<String>,
+}
+
+fn line_connection(stream: TcpStream) -> io::Result<Connection> {
+    let (sender, outgoing) = channel::<ServerMessage>();
+    let (incoming, receiver) = channel();
+    let mut writer = stream.try_clone()?;
+    thread::spawn(move || {
+        for message in outgoing {
+            if writeln!(writer, "{}", message.to_line()).is_err() {
+                break;
+            }
+        }
+        // nothing more will be sent, so hang up
+        let _ = writer.shutdown(Shutdown::Both);
+    });
+    thread::spawn(move || {
+        let incoming = Hangup(incoming);
+        for line in BufReader::new(stream).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if incoming
+                .0
+                .send(Some(ClientMessage::from_line(&line)))
+                .is_err()
+            {
+                return;
+            }
+        }
+    });
+    Ok(Connection {
+        sender,
+        receiver,
+        features: Vec::new(),
+        account: None,
+    })
+}
+
+// tells whoever reads a connection that the client has gone, however its reader thread stops,
+// a panic included, so the table is never left waiting on a reader that is no longer there
+pub(crate) struct Hangup(pub(crate) Sender<Option<Result<ClientMessage, String>>>);
+
+impl Drop for Hangup {
+    fn drop(&mut self) {
+        let _ = self.0.send(None);
+    }
+}
+
+// tables waiting for a second player, and players waiting to be matched
+pub struct Lobby {
+    next_table: u32,
+    open: Vec<WaitingTable>,
+    seekers: Vec<Seeker>,
+    // the seats players can take back at tables that have started
+    sessions: Vec<Session>,
+    // the matches that can be watched
+    playing: Vec<PlayingTable>,
+    reconnection: Reconnection,
+    reveal: Option<Duration>,
+    time_control: TimeControl,
+    accounts: Option<Arc<Mutex<Accounts>>>,
+    correspondence: Correspondence,
+}
+
+struct WaitingTable {
+    number: u32,
+    host: String,
+    options: TableOptions,
+    // the code to join by, for invite only tables
+    invite: Option<String>,
+    connection: Connection,
+}
+
+// a player waiting to be matched with someone of a similar rating
+struct Seeker {
+    name: String,
+    rules: String,
+    rating: f64,
+    since: Instant,
+    connection: Connection,
+}
+
+struct Session {
+    token: String,
+    seat: usize,
+    table: TableEvents,
+}
+
+struct PlayingTable {
+    number: u32,
+    names: [String; 2],
+    table: TableEvents,
+}
+
+impl Seeker {
+    // how far from their own rating they will accept an opponent's
+    fn range(&self) -> f64 {
+        MATCH_RANGE + MATCH_WIDENING * self.since.elapsed().as_secs_f64()
+    }
+}
+
+impl Lobby {
+    pub fn shared() -> Arc<Mutex<Lobby>> {
+        Lobby::create(ServerOptions::standard())
+    }
+
+    pub fn create(options: ServerOptions) -> Arc<Mutex<Lobby>> {
+        let lobby = Arc::new(Mutex::new(Lobby {
+            next_table: 1,
+            open: Vec::new(),
+            seekers: Vec::new(),
+            sessions: Vec::new(),
+            playing: Vec::new(),
+            reconnection: options.reconnection,
+            reveal: options.reveal,
+            time_control: options.time_control,
+            accounts: options
+                .accounts
+                .as_deref()
+                .map(|path| Arc::new(Mutex::new(Accounts::load(path)))),
+            correspondence: Correspondence::load(&options.games, options.webhook),
+        }));
+        // the waiting players' ranges widen as time passes, so they are matched again every
+        // so often, and correspondence deadlines run out, until the lobby goes away
+        let waiting = Arc::downgrade(&lobby);
+        thread::spawn(move || loop {
+            thread::sleep(MATCH_INTERVAL);
+            match waiting.upgrade() {
+                Some(lobby) => {
+                    let mut lobby = lobby.lock().unwrap();
+                    lobby.match_seekers();
+                    lobby.correspondence.check_deadlines();
+                }
+                None => return,
+            }
+        });
+        lobby
+    }
+
+    // the tables anyone can join
+    fn listing(&self) -> Vec<OpenTable> {
+        self.open
+            .iter()
+            .filter(|table| table.invite.is_none())
+            .map(|table| OpenTable {
+                table: table.number,
+                host: table.host.clone(),
+                rules: table.options.rules.clone(),
+                stakes: table.options.stakes,
+            })
+            .collect()
+    }
+
+    // the matches being played, dropping those that have finished
+    fn in_play(&mut self) -> Vec<String> {
+        self.playing
+            .retain(|playing| playing.table.lock().unwrap().is_some());
+        self.playing
+            .iter()
+            .map(|playing| {
+                format!(
+                    "table {} - {} and {} are playing",
+                    playing.number, playing.names[0], playing.names[1]
+                )
+            })
+            .collect()
+    }
+
+    fn table_number(&mut self) -> u32 {
+        let number = self.next_table;
+        self.next_table += 1;
+        number
+    }
+
+    fn open_table(&mut self, host: String, options: TableOptions, connection: Connection) {
+        let number = self.table_number();
+        let _ = connection.sender.send(ServerMessage::Table(number));
+        let invite = if options.private {
+            let code = self.invite_code();
+            let _ = connection.sender.send(ServerMessage::Invite(code.clone()));
+            Some(code)
+        } else {
+            None
+        };
+        self.open.push(WaitingTable {
+            number,
+            host,
+            options,
+            invite,
+            connection,
+        });
+    }
+
+    fn invite_code(&self) -> String {
+        loop {
+            let code = random_code(INVITE_LENGTH);
+            if self
+                .open
+                .iter()
+                .all(|table| table.invite.as_ref() != Some(&code))
+            {
+                return code;
+            }
+        }
+    }
+
+    fn sit_down(&mut self, index: usize, name: String, connection: Connection) {
+        let table = self.open.remove(index);
+        self.start_match(
+            table.number,
+            [(table.host, table.connection), (name, connection)],
+            &table.options,
+        );
+    }
+
+    // seats both players and gives each a session to take their seat back with
+    fn start_match(
+        &mut self,
+        number: u32,
+        seats: [(String, Connection); 2],
+        options: &TableOptions,
+    ) {
+        let rules = RuleSet::named(&options.rules).unwrap_or(RuleSet::standard());
+        let mut table = Table::open(
+            number,
+            seats,
+            rules,
+            options.stakes,
+            self.reconnection,
+            self.reveal,
+            self.time_control,
+        );
+        for seat in 0..2 {
+            let token = random_code(SESSION_LENGTH);
+            table.send(seat, &ServerMessage::Session(token.clone()));
+            self.sessions.push(Session {
+                token,
+                seat,
+                table: table.entrance.clone(),
+            });
+        }
+        // invite only matches are not open to spectators
+        if !options.private {
+            self.playing.push(PlayingTable {
+                number,
+                names: table.names.clone(),
+                table: table.entrance.clone(),
+            });
+        }
+        thread::spawn(move || table.host_match());
+    }
+
+    // hands a spectator to the match at the table, or back if there is none
+    fn watch(&mut self, number: u32, connection: Connection) -> Option<Connection> {
+        let playing = match self.playing.iter().find(|playing| playing.number == number) {
+            Some(playing) => playing,
+            None => return Some(connection),
+        };
+        match enter(&playing.table, TableEvent::Watch(connection)) {
+            Ok(_) => None,
+            Err(TableEvent::Watch(connection)) => Some(connection),
+            Err(_) => None,
+        }
+    }
+
+    // pairs up waiting players under the same rules whose ratings are within both of their
+    // ranges, closest ratings first
+    fn match_seekers(&mut self) {
+        let mut index = 0;
+        while index < self.seekers.len() {
+            let seeker = &self.seekers[index];
+            let opponent = self
+                .seekers
+                .iter()
+                .enumerate()
+                .skip(index + 1)
+                .filter(|(_, other)| {
+                    other.rules == seeker.rules
+                        && other.name != seeker.name
+                        && (other.rating - seeker.rating).abs() <= seeker.range().min(other.range())
+                })
+                .min_by(|(_, a), (_, b)| {
+                    let a = (a.rating - seeker.rating).abs();
+                    let b = (b.rating - seeker.rating).abs();
+                    a.total_cmp(&b)
+                })
+                .map(|(opponent, _)| opponent);
+            match opponent {
+                Some(opponent) => {
+                    let second = self.seekers.remove(opponent);
+                    let first = self.seekers.remove(index);
+                    let options = TableOptions {
+                        rules: first.rules,
+                        ..TableOptions::standard()
+                    };
+                    let number = self.table_number();
+                    self.start_match(
+                        number,
+                        [
+                            (first.name, first.connection),
+                            (second.name, second.connection),
+                        ],
+                        &options,
+                    );
+                }
+                None => index += 1,
+            }
+        }
+    }
+}
+
+pub fn random_code(length: usize) -> String {
+    let mut rng = thread_rng();
+    (0..length)
+        .map(|_| *INVITE_LETTERS.choose(&mut rng).unwrap() as char)
+        .collect()
+}
+
+// the player's rating under the rules, from the saved hands
+fn rating(name: &str, rules: &str) -> f64 {
+    let ladder = Ladder::from_results(&match_results(&HandRecord::load_all()));
+    match ladder.player(name, rules) {
+        Some(player) => player.rating.rating,
+        None => Rating::new().rating,
+    }
+}
+
+// agrees a protocol version and features with a new client, turning it away if it cannot
+// speak a version this server does; returns the features both ends support
+fn handshake(connection: &Connection) -> Option<Vec<String>> {
+    let (version, wanted) = match connection.receiver.recv() {
+        Ok(Some(Ok(ClientMessage::Hello(version, wanted)))) => (version, wanted),
+        Ok(Some(_)) => {
+            refuse(
+                connection,
+                &format!(
+                    "say hello first, this server speaks protocol version {}",
+                    PROTOCOL_VERSION
+                ),
+            );
+            return None;
+        }
+        _ => return None,
+    };
+    if !(OLDEST_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        refuse(
+            connection,
+            &format!(
+                "protocol version {} is not supported, this server speaks versions {} to {}",
+                version, OLDEST_VERSION, PROTOCOL_VERSION
+            ),
+        );
+        return None;
+    }
+    let agreed: Vec<String> = protocol::features()
+        .into_iter()
+        .filter(|feature| wanted.contains(feature))
+        .collect();
+    let _ = connection
+        .sender
+        .send(ServerMessage::Welcome(version, agreed.clone()));
+    Some(agreed)
+}
+
+// takes a new client's name and seats it at a table, or in the queue to be matched
+fn welcome(mut connection: Connection, lobby: Arc<Mutex<Lobby>>) {
+    // dropping the connection closes it
+    let features = match handshake(&connection) {
+        Some(features) => features,
+        None => return,
+    };
+    connection.features = features.clone();
+    let accounts = lobby.lock().unwrap().accounts.clone();
+    let agreed = |rules: &str| features.contains(&protocol::rules_feature(rules));
+    let mut name = String::new();
+    loop {
+        let message = match connection.receiver.recv() {
+            Ok(Some(message)) => message,
+            // gone before sitting down
+            _ => return,
+        };
+        if let Ok(message) = &message {
+            let missing = message
+                .needs()
+                .into_iter()
+                .find(|feature| !features.contains(feature));
+            if let Some(feature) = missing {
+                refuse(
+                    &connection,
+                    &format!("{} was not agreed in the handshake", feature),
+                );
+                continue;
+            }
+        }
+        let request = match message {
+            Ok(ClientMessage::Name(_)) if accounts.is_some() => {
+                refuse(&connection, "this server has accounts, log in or register");
+                continue;
+            }
+            Ok(
+                request @ (ClientMessage::Register(_, _)
+                | ClientMessage::Login(_, _)
+                | ClientMessage::Authenticate(_)),
+            ) => {
+                match log_in(&accounts, request) {
+                    Ok((account, token)) => {
+                        name = account.clone();
+                        connection.account = Some(account.clone());
+                        let _ = connection
+                            .sender
+                            .send(ServerMessage::Account(account, token));
+                    }
+                    Err(e) => refuse(&connection, &e),
+                }
+                continue;
+            }
+            Ok(ClientMessage::Name(new_name)) => {
+                if !protocol::valid_name(&new_name) {
+                    refuse(&connection, "choose another name");
+                } else {
+                    name = new_name;
+                }
+                continue;
+            }
+            Ok(ClientMessage::Resume(session)) => {
+                connection = match resume(&lobby, &session, connection) {
+                    Some(connection) => connection,
+                    None => return,
+                };
+                refuse(&connection, "that session is over");
+                continue;
+            }
+            Ok(_) if name.is_empty() => {
+                refuse(&connection, "send your name first");
+                continue;
+            }
+            Ok(ClientMessage::Hello(_, _)) => {
+                refuse(&connection, "hello was already said");
+                continue;
+            }
+            Ok(
+                ClientMessage::Play(_)
+                | ClientMessage::Chat(_)
+                | ClientMessage::Emote(_)
+                | ClientMessage::Mute(_),
+            ) => {
+                refuse(&connection, "join a table first");
+                continue;
+            }
+            Ok(request) => request,
+            Err(e) => {
+                refuse(&connection, &e);
+                continue;
+            }
+        };
+        let rules = match &request {
+            ClientMessage::Create(options) => Some(&options.rules),
+            ClientMessage::Match(rules) => Some(rules),
+            _ => None,
+        };
+        if let Some(rules) = rules {
+            if RuleSet::named(rules).is_none() {
+                refuse(&connection, &format!("there are no rules called {}", rules));
+                continue;
+            }
+        }
+        // read before taking the lobby, as it means loading every saved hand
+        let seeker_rating = match &request {
+            ClientMessage::Match(rules) => rating(&name, rules),
+            _ => 0.0,
+        };
+
+        let mut lobby = lobby.lock().unwrap();
+        match request {
+            ClientMessage::List => {
+                let _ = connection
+                    .sender
+                    .send(ServerMessage::Tables(lobby.listing()));
+                for text in lobby.in_play() {
+                    let _ = connection.sender.send(ServerMessage::Info(text));
+                }
+                continue;
+            }
+            ClientMessage::Challenge(opponent, rules, hours) => {
+                let deadline = match hours.checked_mul(3600) {
+                    Some(secs) => Duration::from_secs(secs),
+                    None => {
+                        refuse(&connection, &format!("invalid hours: {}", hours));
+                        continue;
+                    }
+                };
+                // with accounts, only a player who has one can be challenged
+                let unknown = lobby
+                    .accounts
+                    .as_ref()
+                    .is_some_and(|accounts| !accounts.lock().unwrap().exists(&opponent));
+                if unknown {
+                    refuse(
+                        &connection,
+                        &format!("there is no player called {}", opponent),
+                    );
+                    continue;
+                }
+                match lobby
+                    .correspondence
+                    .challenge(&name, &opponent, &rules, deadline)
+                {
+                    Ok(game) => {
+                        if let Ok((text, view)) = lobby.correspondence.show(game, &name) {
+                            let _ = connection.sender.send(ServerMessage::Info(text));
+                            let _ = connection.sender.send(ServerMessage::View(view));
+                        }
+                    }
+                    Err(e) => refuse(&connection, &e),
+                }
+                continue;
+            }
+            ClientMessage::Accept(game) => {
+                match lobby.correspondence.accept(game, &name) {
+                    Ok((text, view)) => {
+                        let _ = connection.sender.send(ServerMessage::Info(text));
+                        let _ = connection.sender.send(ServerMessage::View(view));
+                    }
+                    Err(e) => refuse(&connection, &e),
+                }
+                continue;
+            }
+            ClientMessage::Games => {
+                let games = lobby.correspondence.games_of(&name);
+                let _ = connection.sender.send(ServerMessage::Games(games));
+                continue;
+            }
+            ClientMessage::Show(game) => {
+                match lobby.correspondence.show(game, &name) {
+                    Ok((text, view)) => {
+                        let _ = connection.sender.send(ServerMessage::Info(text));
+                        let _ = connection.sender.send(ServerMessage::View(view));
+                    }
+                    Err(e) => refuse(&connection, &e),
+                }
+                continue;
+            }
+            ClientMessage::Post(game, action) => {
+                match lobby.correspondence.post(game, &name, action) {
+                    Ok((text, view)) => {
+                        let _ = connection.sender.send(ServerMessage::View(view));
+                        let _ = connection.sender.send(ServerMessage::Info(text));
+                    }
+                    Err(e) => refuse(&connection, &e),
+                }
+                continue;
+            }
+            ClientMessage::Watch(table) => {
+                if let Some(returned) = lobby.watch(table, connection) {
+                    connection = returned;
+                    refuse(&connection, "there is no match to watch at that table");
+                    continue;
+                }
+            }
+            ClientMessage::Create(options) => lobby.open_table(name, options, connection),
+            ClientMessage::Join(table) => {
+                let found = lobby.open.iter().position(|open| {
+                    open.invite.is_none()
+                        && table.is_none_or(|table| table == open.number)
+                        && open.host != name
+                        && agreed(&open.options.rules)
+                });
+                match (found, table) {
+                    (Some(index), _) => lobby.sit_down(index, name, connection),
+                    (None, Some(_)) => {
+                        refuse(&connection, "there is no such table to join");
+                        continue;
+                    }
+                    // nothing open to join
+                    (None, None) => lobby.open_table(name, TableOptions::standard(), connection),
+                }
+            }
+            ClientMessage::Invite(code) => {
+                let found = lobby.open.iter().position(|open| {
+                    open.invite.as_ref() == Some(&code.to_uppercase())
+                        && open.host != name
+                        && agreed(&open.options.rules)
+                });
+                match found {
+                    Some(index) => lobby.sit_down(index, name, connection),
+                    None => {
+                        refuse(&connection, "there is no table with that invite code");
+                        continue;
+                    }
+                }
+            }
+            ClientMessage::Match(rules) => {
+                let _ = connection.sender.send(ServerMessage::Info(format!(
+                    "looking for an opponent near your {} rating of {:.0}",
+                    rules, seeker_rating
+                )));
+                lobby.seekers.push(Seeker {
+                    name,
+                    rules,
+                    rating: seeker_rating,
+                    since: Instant::now(),
+                    connection,
+                });
+                lobby.match_seekers();
+            }
+            ClientMessage::Hello(_, _)
+            | ClientMessage::Name(_)
+            | ClientMessage::Register(_, _)
+            | ClientMessage::Login(_, _)
+            | ClientMessage::Authenticate(_)
+            | ClientMessage::Resume(_)
+            | ClientMessage::Chat(_)
+            | ClientMessage::Emote(_)
+            | ClientMessage::Mute(_)
+            | ClientMessage::Play(_) => continue,
+        }
+        return;
+    }
+}
+
+// checks a login against the accounts, returning the account and a token for it
+fn log_in(
+    accounts: &Option<Arc<Mutex<Accounts>>>,
+    request: ClientMessage,
+) -> Result<(String, String), String> {
+    let accounts = match accounts {
+        Some(accounts) => accounts,
+        None => return Err(String::from("this server has no accounts, send your name")),
+    };
+    let name = match request {
+        ClientMessage::Register(name, password) => {
+            if !protocol::valid_name(&name) {
+                return Err(String::from("choose another name"));
+            }
+            if accounts.lock().unwrap().exists(&name) {
+                return Err(format!("{} is already taken", name));
+            }
+            let hash = accounts::hash_password(&password)?;
+            accounts.lock().unwrap().add(&name, hash)?;
+            name
+        }
+        ClientMessage::Login(name, password) => {
+            let hash = accounts.lock().unwrap().hash(&name);
+            if !hash.is_some_and(|hash| accounts::verify_password(&password, &hash)) {
+                return Err(String::from("wrong name or password"));
+            }
+            name
+        }
+        ClientMessage::Authenticate(token) => match accounts.lock().unwrap().authenticate(&token) {
+            Some(name) => name,
+            None => return Err(String::from("that login has expired, log in again")),
+        },
+        _ => return Err(String::from("that is not a login")),
+    };
+    let token = accounts.lock().unwrap().issue_token(&name);
+    Ok((name, token))
+}
+
+fn refuse(connection: &Connection, text: &str) {
+    let _ = connection
+        .sender
+        .send(ServerMessage::Error(String::from(text)));
+}
+
+// hands the connection to the table holding the session's seat, or back if there is none
+fn resume(lobby: &Arc<Mutex<Lobby>>, token: &str, connection: Connection) -> Option<Connection> {
+    let mut lobby = lobby.lock().unwrap();
+    let index = match lobby
+        .sessions
+        .iter()
+        .position(|session| session.token == token)
+    {
+        Some(index) => index,
+        None => return Some(connection),
+    };
+    let session = &lobby.sessions[index];
+    match enter(&session.table, TableEvent::Rejoin(session.seat, connection)) {
+        Ok(_) => None,
+        // the match has finished
+        Err(TableEvent::Rejoin(_, connection)) => {
+            lobby.sessions.remove(index);
+            Some(connection)
+        }
+        Err(_) => None,
+    }
+}
+
+// passes the event to the table, or hands it back if the match is over
+fn enter(table: &TableEvents, event: TableEvent) -> Result<(), TableEvent> {
+    match table.lock().unwrap().as_ref() {
+        Some(events) => events.send(event).map_err(|SendError(event)| event),
+        None => Err(event),
+    }
+}
+
+// what reaches a table: a message from a seat's client, tagged with which of the seat's
+// connections it came on, a new connection for a seat or a spectator
+enum TableEvent {
+    Message(usize, u32, Option<Result<ClientMessage, String>>),
+    Rejoin(usize, Connection),
+    Watch(Connection),
+}
+
+// where the lobby hands new connections to a table, None once the match is over
+type TableEvents = Arc<Mutex<Option<Sender<TableEvent>>>>;
+
+// a message from the client at a seat
+// what a table waits for next
+enum Received {
+    Message(usize, Result<ClientMessage, String>),
+    // a seat has changed hands between a bot and its player
+    Reseated,
+    // the seat whose turn it is has run out of time
+    TimedOut(usize),
+}
+
+// someone watching a match
+struct Spectator {
+    sender: Sender<ServerMessage>,
+    // whether they are shown the players' chat
+    chat: bool,
+    // both players' views, each passed on once it is due
+    hands: Option<Sender<(Instant, ServerMessage)>>,
+}
+
+struct Table {
+    number: u32,
+    names: [String; 2],
+    rules: RuleSet,
+    // what each point of the winning margin is worth
+    stakes: u32,
+    senders: [Sender<ServerMessage>; 2],
+    events: Sender<TableEvent>,
+    receiver: Receiver<TableEvent>,
+    entrance: TableEvents,
+    // counts each seat's connections, so messages from one that has been replaced are ignored
+    connections: [u32; 2],
+    // when each seat's player lost their connection, while the seat is held for them
+    gone: [Option<Instant>; 2],
+    bots: [bool; 2],
+    // the seat whose player forfeited the match by not coming back
+    forfeited: Option<usize>,
+    reconnection: Reconnection,
+    // everything sent to each seat this hand and the latest view, for a player who comes back
+    this_hand: [Vec<ServerMessage>; 2],
+    views: [Option<PlayerView>; 2],
+    spectators: Vec<Spectator>,
+    reveal: Option<Duration>,
+    clock: Clock,
+    // whether each seat's client takes chat, and has muted the opponent's
+    chat: [bool; 2],
+    muted: [bool; 2],
+    // when each seat last said something, for as long as it counts against them
+    said: [Vec<Instant>; 2],
+    // what has been said this hand, to be saved with it
+    chat_log: Vec<ChatLine>,
+    // the accounts the players logged in as, on a server with accounts
+    accounts: [Option<String>; 2],
+}
+
+impl Table {
+    fn open(
+        number: u32,
+        seats: [(String, Connection); 2],
+        rules: RuleSet,
+        stakes: u32,
+        reconnection: Reconnection,
+        reveal: Option<Duration>,
+        time_control: TimeControl,
+    ) -> Self {
+        let (events, receiver) = channel();
+        let [(first_name, first), (second_name, second)] = seats;
+        let mut table = Table {
+            number,
+            names: [first_name, second_name],
+            rules,
+            stakes,
+            senders: [first.sender, second.sender],
+            entrance: Arc::new(Mutex::new(Some(events.clone()))),
+            events,
+            receiver,
+            connections: [0, 0],
+            gone: [None, None],
+            bots: [false, false],
+            forfeited: None,
+            reconnection,
+            this_hand: [Vec::new(), Vec::new()],
+            views: [None, None],
+            spectators: Vec::new(),
+            reveal,
+            clock: Clock::new(time_control),
+            chat: [
+                first.features.iter().any(|feature| feature == CHAT),
+                second.features.iter().any(|feature| feature == CHAT),
+            ],
+            muted: [false, false],
+            said: [Vec::new(), Vec::new()],
+            chat_log: Vec::new(),
+            accounts: [first.account, second.account],
+        };
+        table.listen(0, first.receiver);
+        table.listen(1, second.receiver);
+        let names = format!(
+            "{} and {} sit down to a {} match at stakes of {} at table {}",
+            table.names[0], table.names[1], table.rules.name, table.stakes, table.number
+        );
+        table.broadcast(&ServerMessage::Info(names));
+        if time_control.is_timed() {
+            table.broadcast(&ServerMessage::Info(time_control.describe()));
+        }
+        table
+    }
+
+    // passes the messages from the seat's latest connection on to the table
+    fn listen(&self, seat: usize, client: Receiver<Option<Result<ClientMessage, String>>>) {
+        let events = self.events.clone();
+        let connection = self.connections[seat];
+        thread::spawn(move || {
+            for message in client {
+                let disconnected = message.is_none();
+                let event = TableEvent::Message(seat, connection, message);
+                if events.send(event).is_err() || disconnected {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn send(&mut self, seat: usize, message: &ServerMessage) {
+        let chat = matches!(message, ServerMessage::Chat(..) | ServerMessage::Emote(..));
+        if chat && !self.chat[seat] {
+            return;
+        }
+        match message {
+            ServerMessage::View(view) => self.views[seat] = Some(view.clone()),
+            _ => self.this_hand[seat].push(message.clone()),
+        }
+        // a client that has gone away is noticed when its messages stop
+        let _ = self.senders[seat].send(message.clone());
+    }
+
+    fn broadcast(&mut self, message: &ServerMessage) {
+        self.send(0, message);
+        self.send(1, message);
+        self.spectate(message);
+    }
+
+    // the next message from either client. The game and its clock wait while a seat is held
+    // for a player who has lost their connection; an error ends the match.
+    fn receive(&mut self) -> Result<Received, String> {
+        loop {
+            let held = self
+                .gone
+                .iter()
+                .flatten()
+                .min()
+                .map(|since| *since + self.reconnection.timeout);
+            let event = match held.or(self.clock.deadline()) {
+                Some(deadline) => {
+                    match self
+                        .receiver
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) if held.is_some() => {
+                            self.give_up_seat()?;
+                            return Ok(Received::Reseated);
+                        }
+                        Err(RecvTimeoutError::Timeout) => match self.clock.expire() {
+                            Some(seat) => return Ok(Received::TimedOut(seat)),
+                            None => continue,
+                        },
+                        Err(RecvTimeoutError::Disconnected) => unreachable!(),
+                    }
+                }
+                // the table holds a sender itself, so this never fails
+                None => self.receiver.recv().unwrap(),
+            };
+            match event {
+                TableEvent::Message(seat, connection, _)
+                    if connection != self.connections[seat] => {}
+                TableEvent::Message(seat, _, None) => self.lose(seat),
+                TableEvent::Message(seat, _, Some(message)) => {
+                    match self.gone.iter().position(|since| since.is_some()) {
+                        Some(absent) => {
+                            let text =
+                                format!("the game is paused until {} is back", self.names[absent]);
+                            self.send(seat, &ServerMessage::Error(text));
+                        }
+                        None => return Ok(Received::Message(seat, message)),
+                    }
+                }
+                TableEvent::Rejoin(seat, connection) => {
+                    let from_bot = self.bots[seat];
+                    self.rejoin(seat, connection);
+                    if from_bot {
+                        return Ok(Received::Reseated);
+                    }
+                }
+                TableEvent::Watch(connection) => self.watch(connection),
+            }
+        }
+    }
+
+    fn lose(&mut self, seat: usize) {
+        // a bot is already playing for them
+        if self.bots[seat] {
+            return;
+        }
+        self.gone[seat] = Some(Instant::now());
+        self.clock.pause();
+        let text = format!(
+            "{} has lost their connection, the game is paused for up to {} seconds",
+            self.names[seat],
+            self.reconnection.timeout.as_secs()
+        );
+        self.send(1 - seat, &ServerMessage::Info(text));
+    }
+
+    // seats the new connection and catches the player up on the hand so far
+    fn rejoin(&mut self, seat: usize, connection: Connection) {
+        self.connections[seat] += 1;
+        self.chat[seat] = connection.features.iter().any(|feature| feature == CHAT);
+        self.senders[seat] = connection.sender;
+        self.listen(seat, connection.receiver);
+        self.gone[seat] = None;
+        self.bots[seat] = false;
+        if self.gone.iter().all(|since| since.is_none()) {
+            self.clock.resume();
+        }
+
+        let sender = &self.senders[seat];
+        let _ = sender.send(ServerMessage::Info(String::from(
+            "you are back at the table",
+        )));
+        for message in self.this_hand[seat].iter() {
+            let _ = sender.send(message.clone());
+        }
+        if let Some(view) = &self.views[seat] {
+            let _ = sender.send(ServerMessage::View(view.clone()));
+        }
+        let text = format!("{} is back", self.names[seat]);
+        self.send(1 - seat, &ServerMessage::Info(text));
+    }
+
+    fn watch(&mut self, connection: Connection) {
+        let chat = connection.features.iter().any(|feature| feature == CHAT);
+        let sender = connection.sender;
+        let _ = sender.send(ServerMessage::Info(format!(
+            "you are watching {} and {} at table {}",
+            self.names[0], self.names[1], self.number
+        )));
+        let hands = self.reveal.map(|reveal| {
+            let _ = sender.send(ServerMessage::Info(format!(
+                "you see both hands {} seconds behind the play",
+                reveal.as_secs_f64()
+            )));
+            let hands = delayed(sender.clone());
+            let due = Instant::now() + reveal;
+            for view in self.views.iter().flatten() {
+                let _ = hands.send((due, ServerMessage::Watched(view.clone())));
+            }
+            hands
+        });
+        // where the hand stands, as anyone at the table can see it
+        if let Some(view) = &self.views[0] {
+            let top = match view.discard_pile.last() {
+                Some(card) => card.show(CardStyle::current()),
+                None => String::from("nothing"),
+            };
+            let _ = sender.send(ServerMessage::Info(format!(
+                "{} is on the discard pile, {} cards are left in the stock",
+                top, view.stock
+            )));
+        }
+        self.spectators.push(Spectator {
+            sender,
+            chat,
+            hands,
+        });
+    }
+
+    // passes a public message on to the spectators as it happens
+    fn spectate(&mut self, message: &ServerMessage) {
+        let chat = matches!(message, ServerMessage::Chat(..) | ServerMessage::Emote(..));
+        self.spectators.retain(|spectator| {
+            (chat && !spectator.chat) || spectator.sender.send(message.clone()).is_ok()
+        });
+    }
+
+    // shows a player's view to the spectators who see the hands once it is due, and to the
+    // others when there is nothing secret left in it
+    fn show(&mut self, view: &PlayerView, laid_down: bool) {
+        let watched = ServerMessage::Watched(view.clone());
+        let due = Instant::now() + self.reveal.unwrap_or_default();
+        self.spectators.retain(|spectator| match &spectator.hands {
+            Some(hands) => hands.send((due, watched.clone())).is_ok(),
+            None if laid_down => spectator.sender.send(watched.clone()).is_ok(),
+            None => true,
+        });
+    }
+
+    // the player who has been gone the longest has run out of time
+    fn give_up_seat(&mut self) -> Result<(), String> {
+        let seat = match self.gone {
+            [Some(first), Some(second)] if second < first => 1,
+            [Some(_), _] => 0,
+            _ => 1,
+        };
+        self.gone[seat] = None;
+        if self.gone.iter().all(|since| since.is_none()) {
+            self.clock.resume();
+        }
+        let other = 1 - seat;
+        // a bot only plays on against someone who is still there
+        if self.reconnection.bot && self.gone[other].is_none() && !self.bots[other] {
+            self.bots[seat] = true;
+            let text = format!(
+                "{} has not come back, a bot plays for them",
+                self.names[seat]
+            );
+            self.send(other, &ServerMessage::Info(text));
+            Ok(())
+        } else {
+            let text = format!(
+                "{} has not come back and forfeits the match",
+                self.names[seat]
+            );
+            self.send(other, &ServerMessage::Info(text));
+            self.forfeited = Some(seat);
+            Err(String::from("a player forfeited"))
+        }
+    }
+
+    fn host_match(&mut self) {
+        self.play_match();
+        // no more connections can reach the table, so turn away any already on their way
+        *self.entrance.lock().unwrap() = None;
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                TableEvent::Rejoin(_, connection) => refuse(&connection, "that session is over"),
+                TableEvent::Watch(connection) => refuse(&connection, "that match is over"),
+                TableEvent::Message(..) => {}
+            }
+        }
+    }
+
+    fn play_match(&mut self) {
+        let match_id = new_match_id();
+        let rules = self.rules.clone();
+        let mut totals = [0, 0];
+        let mut hands = 0;
+
+        while totals.iter().all(|total| *total < rules.target_score) {
+            let mut game = GinGame::new(self.names[0].clone(), self.names[1].clone());
+            game.first_player.account = self.accounts[0].clone();
+            game.second_player.account = self.accounts[1].clone();
+            game.rules = rules.clone();
+            game.decide_first_turn();
+            game.deal_starting_hands();
+            self.this_hand = [Vec::new(), Vec::new()];
+            self.chat_log.clear();
+            self.broadcast(&ServerMessage::Info(format!(
+                "new hand, {} goes first",
+                game.current_turn
+            )));
+
+            if self.play_hand(&mut game).is_err() {
+                if let Some(seat) = self.forfeited {
+                    self.forfeit_match(game, match_id, hands + 1, 1 - seat, totals[1 - seat]);
+                }
+                return;
+            }
+            hands += 1;
+            let mut record = game.record(match_id);
+            record.chat = mem::take(&mut self.chat_log);
+            record.save(hands);
+
+            if let Some(seat) = self
+                .names
+                .iter()
+                .position(|name| *name == game.score.player)
+            {
+                totals[seat] += game.score.points;
+            }
+            self.broadcast(&ServerMessage::Info(format!(
+                "{} {}, {} {}",
+                self.names[0], totals[0], self.names[1], totals[1]
+            )));
+        }
+
+        let winner = if totals[0] >= rules.target_score {
+            0
+        } else {
+            1
+        };
+        let margin = totals[winner] - totals[1 - winner];
+        self.broadcast(&ServerMessage::Info(format!(
+            "{} wins the match by {} points, {} at stakes of {}",
+            self.names[winner],
+            margin,
+            margin * self.stakes as i32,
+            self.stakes
+        )));
+    }
+
+    // saves the hand in progress as won by the seat whose opponent forfeited, with the points
+    // they still needed, so the match counts as decided in the results and ratings
+    fn forfeit_match(
+        &mut self,
+        mut game: GinGame,
+        match_id: u64,
+        hand: usize,
+        winner: usize,
+        total: i32,
+    ) {
+        game.set_score(self.rules.target_score - total, self.names[winner].clone());
+        let mut record = game.record(match_id);
+        record.chat = mem::take(&mut self.chat_log);
+        record.save(hand);
+        self.broadcast(&ServerMessage::Info(format!(
+            "{} wins the match by forfeit",
+            self.names[winner]
+        )));
+    }
+
+    fn play_hand(&mut self, game: &mut GinGame) -> Result<(), String> {
+        self.send_views(game);
+        while game.phase == Phase::Draw || game.phase == Phase::Discard {
+            let turn = if game.current_turn == self.names[0] {
+                0
+            } else {
+                1
+            };
+            self.clock.turn(turn);
+            let (seat, message) = if self.bots[turn] {
+                (turn, Ok(ClientMessage::Play(game.bot_action())))
+            } else {
+                match self.receive()? {
+                    Received::Message(seat, message) => (seat, message),
+                    // whoever is now in the seat needs to see where the game is
+                    Received::Reseated => {
+                        self.send_views(game);
+                        continue;
+                    }
+                    Received::TimedOut(seat) => {
+                        self.time_out(game, seat);
+                        continue;
+                    }
+                }
+            };
+            let action = match message {
+                Ok(ClientMessage::Play(action)) => action,
+                Ok(ClientMessage::Chat(text)) => {
+                    self.say(game, seat, text, false);
+                    continue;
+                }
+                Ok(ClientMessage::Emote(emote)) => {
+                    self.say(game, seat, emote, true);
+                    continue;
+                }
+                Ok(ClientMessage::Mute(muted)) => {
+                    self.mute(seat, muted);
+                    continue;
+                }
+                Ok(_) => {
+                    self.send(
+                        seat,
+                        &ServerMessage::Error(String::from("the match has already started")),
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    self.send(seat, &ServerMessage::Error(e));
+                    continue;
+                }
+            };
+            if let Err(e) = self.play(game, seat, action) {
+                self.send(seat, &ServerMessage::Error(e));
+            }
+        }
+        self.clock.stop();
+
+        // otherwise the stock ran out and nobody lays down
+        if game.phase == Phase::Showdown {
+            game.showdown();
+            self.send_views(game);
+        }
+        self.broadcast(&ServerMessage::Result(
+            game.score.player.clone(),
+            game.score.points,
+        ));
+        Ok(())
+    }
+
+    // passes on what the seat says to the table, unless they are saying too much, and keeps it
+    // for the hand's log
+    fn say(&mut self, game: &GinGame, seat: usize, text: String, emote: bool) {
+        let refused = if emote && !EMOTES.contains(&text.as_str()) {
+            Some(format!(
+                "there is no emote called {}, try one of {}",
+                text,
+                EMOTES.join(", ")
+            ))
+        } else if text.trim().is_empty()
+            || text.chars().count() > CHAT_LENGTH
+            || text.chars().any(|c| c.is_control())
+        {
+            Some(format!(
+                "say something on one line of up to {} characters",
+                CHAT_LENGTH
+            ))
+        } else {
+            self.said[seat].retain(|said| said.elapsed() < CHAT_WINDOW);
+            if self.said[seat].len() >= CHAT_LIMIT {
+                Some(String::from(
+                    "you are chatting too fast, wait a few seconds",
+                ))
+            } else {
+                None
+            }
+        };
+        if let Some(refused) = refused {
+            self.send(seat, &ServerMessage::Error(refused));
+            return;
+        }
+        self.said[seat].push(Instant::now());
+
+        let name = self.names[seat].clone();
+        self.chat_log.push(ChatLine {
+            after: game.moves.len(),
+            player: name.clone(),
+            text: text.clone(),
+            emote,
+        });
+        let message = if emote {
+            ServerMessage::Emote(name, text)
+        } else {
+            ServerMessage::Chat(name, text)
+        };
+        self.send(seat, &message);
+        if !self.muted[1 - seat] {
+            self.send(1 - seat, &message);
+        }
+        self.spectate(&message);
+    }
+
+    fn mute(&mut self, seat: usize, muted: bool) {
+        self.muted[seat] = muted;
+        let text = if muted {
+            format!("{}'s chat is muted", self.names[1 - seat])
+        } else {
+            format!("{}'s chat is no longer muted", self.names[1 - seat])
+        };
+        self.send(seat, &ServerMessage::Info(text));
+    }
+
+    // makes the seat's move if the rules allow it
+    fn play(&mut self, game: &mut GinGame, seat: usize, action: Action) -> Result<(), String> {
+        let player_move = Move {
+            player: self.names[seat].clone(),
+            action: action.clone(),
+        };
+        game.check_rules(&player_move)?;
+        game.apply_move(player_move);
+        let moved = ServerMessage::Moved(self.names[seat].clone(), action);
+        self.spectate(&moved);
+        self.send(1 - seat, &moved);
+        self.send_views(game);
+        Ok(())
+    }
+
+    // settles a turn that has run out of time as the time control says
+    fn time_out(&mut self, game: &mut GinGame, seat: usize) {
+        let name = self.names[seat].clone();
+        if self.clock.policy() == Timeout::Play {
+            self.broadcast(&ServerMessage::Info(format!(
+                "{} has run out of time, their turn is played for them",
+                name
+            )));
+            let playing = |game: &GinGame| {
+                game.current_turn == name
+                    && (game.phase == Phase::Draw || game.phase == Phase::Discard)
+            };
+            while playing(game) {
+                let action = game.timeout_action();
+                if self.play(game, seat, action).is_err() {
+                    break;
+                }
+            }
+            // otherwise nothing could be thrown, so the hand is lost after all
+            if !playing(game) {
+                return;
+            }
+        }
+        self.broadcast(&ServerMessage::Info(format!(
+            "{} has run out of time and forfeits the hand",
+            name
+        )));
+        game.forfeit_hand(&name);
+        self.send_views(game);
+    }
+
+    fn send_views(&mut self, game: &GinGame) {
+        // once the hands are laid down they are no secret
+        let laid_down = game.phase == Phase::Over && (game.knock_status || game.gin_status);
+        for seat in 0..2 {
+            let view = game.view(&self.names[seat]);
+            self.show(&view, laid_down);
+            self.send(seat, &ServerMessage::View(view));
+        }
+    }
+}
+
+// passes each message on to the sender once it is due
+fn delayed(sender: Sender<ServerMessage>) -> Sender<(Instant, ServerMessage)> {
+    let (delayed, receiver) = channel::<(Instant, ServerMessage)>();
+    thread::spawn(move || {
+        for (due, message) in receiver {
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+    });
+    delayed
+}
@@ -0,0 +1,132 @@
+// A mental poker shuffle, so two peers can deal a fair hand without a trusted dealer. Each peer
+// picks a secret key for a commutative cipher (exponentiation modulo a shared prime), encrypts
+// every card in the deck and shuffles it, one after the other. Nobody then knows the order of
+// the deck, and a card can only be read once the other peer has taken their lock off it. Keys
+// are committed to before the shuffle and revealed when the hand ends, so each peer can check
+// the other shuffled an honest deck and told the truth about every card.
+
+use num_bigint::{BigUint, RandBigInt};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use sha2::{Digest, Sha256};
+
+use crate::{Card, Deck};
+
+// the 2048-bit safe prime from RFC 3526, group 14
+const PRIME: &str = "\
+FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05\
+98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB\
+9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718\
+3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF";
+
+fn prime() -> BigUint {
+    BigUint::parse_bytes(PRIME.as_bytes(), 16).unwrap()
+}
+
+pub struct ShuffleKey {
+    encrypt: BigUint,
+    decrypt: BigUint,
+    // keeps the commitment from giving the key away
+    nonce: BigUint,
+}
+
+impl ShuffleKey {
+    pub fn generate() -> Self {
+        let order = prime() - 1u32;
+        let mut rng = thread_rng();
+        loop {
+            let encrypt = rng.gen_biguint_range(&BigUint::from(3u32), &order);
+            if let Some(decrypt) = encrypt.modinv(&order) {
+                let nonce = rng.gen_biguint(256);
+                return ShuffleKey {
+                    encrypt,
+                    decrypt,
+                    nonce,
+                };
+            }
+        }
+    }
+
+    // the opponent's key once they have revealed it, checked against what they committed to
+    pub fn reveal(encrypt: BigUint, nonce: BigUint, commitment: &str) -> Result<Self, String> {
+        let decrypt = match encrypt.modinv(&(prime() - 1u32)) {
+            Some(decrypt) => decrypt,
+            None => return Err(String::from("the revealed key cannot be undone")),
+        };
+        let key = ShuffleKey {
+            encrypt,
+            decrypt,
+            nonce,
+        };
+        if key.commitment() != commitment {
+            return Err(String::from("the revealed key is not the one committed to"));
+        }
+        Ok(key)
+    }
+
+    // a sha256 hash of the key, sent before the shuffle
+    pub fn commitment(&self) -> String {
+        let hash = Sha256::digest(format!("{:x}:{:x}", self.encrypt, self.nonce));
+        hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn encrypt_key(&self) -> &BigUint {
+        &self.encrypt
+    }
+
+    pub fn nonce(&self) -> &BigUint {
+        &self.nonce
+    }
+
+    pub fn encrypt(&self, value: &BigUint) -> BigUint {
+        value.modpow(&self.encrypt, &prime())
+    }
+
+    pub fn decrypt(&self, value: &BigUint) -> BigUint {
+        value.modpow(&self.decrypt, &prime())
+    }
+}
+
+// the cards of a new deck as numbers. They are squares, as the cipher would give away whether
+// a card was a square or not.
+pub fn card_values() -> Vec<BigUint> {
+    (0..52u32)
+        .map(|index| BigUint::from((index + 2) * (index + 2)))
+        .collect()
+}
+
+pub fn card(value: &BigUint) -> Result<Card, String> {
+    match card_values().iter().position(|card| card == value) {
+        Some(index) => Ok(Deck::create().cards.remove(index)),
+        None => Err(String::from("a card was not unlocked properly")),
+    }
+}
+
+pub fn encrypt_and_shuffle(values: &[BigUint], key: &ShuffleKey) -> Vec<BigUint> {
+    let mut encrypted: Vec<BigUint> = values.iter().map(|value| key.encrypt(value)).collect();
+    encrypted.shuffle(&mut thread_rng());
+    encrypted
+}
+
+// whether output is the input encrypted with key, in any order
+pub fn check_shuffle(input: &[BigUint], output: &[BigUint], key: &ShuffleKey) -> bool {
+    let mut expected: Vec<BigUint> = input.iter().map(|value| key.encrypt(value)).collect();
+    let mut output = output.to_vec();
+    expected.sort();
+    output.sort();
+    expected == output
+}
+
+// takes both locks off every card in the deck
+pub fn open(deck: &[BigUint], keys: [&ShuffleKey; 2]) -> Result<Vec<Card>, String> {
+    let cards = deck
+        .iter()
+        .map(|value| card(&keys[0].decrypt(&keys[1].decrypt(value))))
+        .collect::<Result<Vec<Card>, String>>()?;
+    Deck::check_pack(&cards)?;
+    Ok(cards)
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use crate::record::{account_names, HandRecord};
+use crate::{Action, Phase, RuleSet};
+
+// gin stats <player>
+pub fn run(args: &[String]) {
+    let name = match args.first() {
+        Some(name) => name,
+        None => {
+            println!("usage: gin stats <player>");
+            return;
+        }
+    };
+    let profile = PlayerProfile::from_records(name, &HandRecord::load_all());
+    if profile.hands_played == 0 {
+        println!("no finished hands for {}", name);
+        return;
+    }
+    profile.display();
+}
+
+// how a finished hand ended
+pub struct HandSummary {
+    pub knocker: String,
+    pub gin: bool,
+    pub undercut: bool,
+    pub winner: String,
+    pub points: i32,
+    pub knocker_deadwood: i32,
+    pub defender_deadwood: i32,
+    pub turns: u32,
+}
+
+impl HandSummary {
+    // None for hands that were never finished or cannot be played back
+    pub fn from_record(record: &HandRecord) -> Option<HandSummary> {
+        if record.winner.is_empty() {
+            return None;
+        }
+        let going_down = record
+            .moves
+            .iter()
+            .find(|player_move| matches!(player_move.action, Action::Knock | Action::Gin))?;
+        let game = record.play_to(record.moves.len()).ok()?;
+        // a hand forfeited before the discard that goes down says nothing of how it would end
+        if game.phase != Phase::Showdown {
+            return None;
+        }
+        let (knocker, defender) = if going_down.player == game.first_player.name {
+            (&game.first_player, &game.second_player)
+        } else {
+            (&game.second_player, &game.first_player)
+        };
+        let gin = going_down.action == Action::Gin;
+        let turns = record
+            .moves
+            .iter()
+            .filter(|player_move| {
+                matches!(player_move.action, Action::DrawStock | Action::DrawDiscard)
+            })
+            .count() as u32;
+
+        Some(HandSummary {
+            knocker: knocker.name.clone(),
+            gin,
+            undercut: !gin && record.winner != knocker.name,
+            winner: record.winner.clone(),
+            points: record.points,
+            knocker_deadwood: knocker.deadwood(),
+            defender_deadwood: defender.deadwood(),
+            turns,
+        })
+    }
+}
+
+pub struct MatchResult {
+    pub match_id: u64,
+    pub rules: String,
+    pub first_player: String,
+    pub second_player: String,
+    // None until someone reaches the target score
+    pub winner: Option<String>,
+    // when the deciding hand, or the latest hand so far, was played
+    pub played: u64,
+}
+
+// groups the saved hands into matches, in the order the matches were decided. Records should
+// be in the order they were played, as HandRecord::load_all returns them. Hands where either
+// seat used an account's name without logging in to it are left out.
+pub fn match_results(records: &[HandRecord]) -> Vec<MatchResult> {
+    let mut results: Vec<MatchResult> = Vec::new();
+    // match id -> (first player's total, second player's total)
+    let mut totals: HashMap<u64, (i32, i32)> = HashMap::new();
+    let accounts = account_names(records);
+
+    for record in records.iter() {
+        if record.match_id == 0 || record.winner.is_empty() {
+            continue;
+        }
+        if !record.played_as(&record.first_player, &accounts)
+            || !record.played_as(&record.second_player, &accounts)
+        {
+            continue;
+        }
+        let index = match results
+            .iter()
+            .position(|result| result.match_id == record.match_id)
+        {
+            Some(index) => index,
+            None => {
+                results.push(MatchResult {
+                    match_id: record.match_id,
+                    rules: record.rules.clone(),
+                    first_player: record.first_player.clone(),
+                    second_player: record.second_player.clone(),
+                    winner: None,
+                    played: record.played,
+                });
+                results.len() - 1
+            }
+        };
+        let result = &mut results[index];
+        if result.winner.is_some() {
+            continue;
+        }
+        result.played = record.played;
+
+        let (first_total, second_total) = totals.entry(record.match_id).or_insert((0, 0));
+        if record.winner == result.first_player {
+            *first_total += record.points;
+        } else {
+            *second_total += record.points;
+        }
+        let target_score = match RuleSet::named(&result.rules) {
+            Some(rules) => rules.target_score,
+            None => RuleSet::standard().target_score,
+        };
+        if *first_total >= target_score {
+            result.winner = Some(result.first_player.clone());
+        } else if *second_total >= target_score {
+            result.winner = Some(result.second_player.clone());
+        }
+    }
+
+    results.sort_by_key(|result| result.played);
+    results
+}
+
+pub struct PlayerProfile {
+    pub name: String,
+    pub matches_played: u32,
+    pub matches_won: u32,
+    pub hands_played: u32,
+    pub hands_won: u32,
+    pub gins: u32,
+    // knocks that were not gin
+    pub knocks: u32,
+    pub opponent_knocks: u32,
+    pub undercuts_given: u32,
+    pub undercuts_received: u32,
+    pub knock_deadwood: i32,
+    pub turns: u32,
+    pub points: i32,
+}
+
+impl PlayerProfile {
+    pub fn new(name: &str) -> Self {
+        PlayerProfile {
+            name: String::from(name),
+            matches_played: 0,
+            matches_won: 0,
+            hands_played: 0,
+            hands_won: 0,
+            gins: 0,
+            knocks: 0,
+            opponent_knocks: 0,
+            undercuts_given: 0,
+            undercuts_received: 0,
+            knock_deadwood: 0,
+            turns: 0,
+            points: 0,
+        }
+    }
+
+    pub fn from_records(name: &str, records: &[HandRecord]) -> Self {
+        let mut profile = PlayerProfile::new(name);
+        let accounts = account_names(records);
+        for record in records.iter() {
+            if record.first_player != name && record.second_player != name {
+                continue;
+            }
+            if !record.played_as(name, &accounts) {
+                continue;
+            }
+            if let Some(summary) = HandSummary::from_record(record) {
+                profile.add_hand(&summary);
+            }
+        }
+        for result in match_results(records).iter() {
+            if result.first_player != name && result.second_player != name {
+                continue;
+            }
+            profile.matches_played += 1;
+            if result.winner.as_deref() == Some(name) {
+                profile.matches_won += 1;
+            }
+        }
+        profile
+    }
+
+    pub fn add_hand(&mut self, summary: &HandSummary) {
+        self.hands_played += 1;
+        self.turns += summary.turns;
+        if summary.winner == self.name {
+            self.hands_won += 1;
+            self.points += summary.points;
+        }
+        if summary.knocker == self.name {
+            if summary.gin {
+                self.gins += 1;
+            } else {
+                self.knocks += 1;
+                self.knock_deadwood += summary.knocker_deadwood;
+                if summary.undercut {
+                    self.undercuts_received += 1;
+                }
+            }
+        } else if !summary.gin {
+            self.opponent_knocks += 1;
+            if summary.undercut {
+                self.undercuts_given += 1;
+            }
+        }
+    }
+
+    pub fn gin_rate(&self) -> f64 {
+        ratio(self.gins, self.hands_played)
+    }
+
+    pub fn knock_rate(&self) -> f64 {
+        ratio(self.knocks, self.hands_played)
+    }
+
+    // share of the opponent's knocks this player undercut
+    pub fn undercut_given_rate(&self) -> f64 {
+        ratio(self.undercuts_given, self.opponent_knocks)
+    }
+
+    // share of this player's knocks that were undercut
+    pub fn undercut_received_rate(&self) -> f64 {
+        ratio(self.undercuts_received, self.knocks)
+    }
+
+    pub fn average_knock_deadwood(&self) -> f64 {
+        if self.knocks == 0 {
+            return 0.0;
+        }
+        self.knock_deadwood as f64 / self.knocks as f64
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        ratio(self.turns, self.hands_played)
+    }
+
+    pub fn points_per_hand(&self) -> f64 {
+        if self.hands_played == 0 {
+            return 0.0;
+        }
+        self.points as f64 / self.hands_played as f64
+    }
+
+    pub fn display(&self) {
+        println!("{}", self.name);
+        println!(
+            "matches won: {} of {}",
+            self.matches_won, self.matches_played
+        );
+        println!("hands won: {} of {}", self.hands_won, self.hands_played);
+        println!("gin rate: {:.1}%", self.gin_rate() * 100.0);
+        println!("knock rate: {:.1}%", self.knock_rate() * 100.0);
+        println!(
+            "undercuts given: {} ({:.1}% of opponent knocks)",
+            self.undercuts_given,
+            self.undercut_given_rate() * 100.0
+        );
+        println!(
+            "undercuts received: {} ({:.1}% of knocks)",
+            self.undercuts_received,
+            self.undercut_received_rate() * 100.0
+        );
+        println!(
+            "average deadwood at knock: {:.1}",
+            self.average_knock_deadwood()
+        );
+        println!("average hand length: {:.1} turns", self.average_turns());
+        println!("points per hand: {:.1}", self.points_per_hand());
+    }
+}
+
+fn ratio(count: u32, total: u32) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    count as f64 / total as f64
+}
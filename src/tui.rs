@@ -0,0 +1,371 @@
+// gin tui [rules]: plays a match against the bot full screen. The hand is laid out in the melds
+// that leave the least deadwood with the rest after them, a card is picked with the arrow keys
+// and every action is a single key. Only what the player's view holds is ever drawn.
+
+use std::io;
+use std::thread;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::bot::{BotMatch, BOT_PAUSE};
+use crate::cards::CardStyle;
+use crate::record::new_match_id;
+use crate::replay::describe_move;
+use crate::{meld, Action, Card, Move, Phase, RuleSet, RANK_VALUES, SUITS};
+
+const KEYS: &str =
+    "←/→ choose a card   s stock   d discard pile   enter discard   k knock   g gin   q quit";
+
+const USAGE: &str = "usage: gin tui [rules]";
+
+pub fn run(args: &[String]) {
+    let rules = match args.first() {
+        Some(name) => match RuleSet::named(name) {
+            Some(rules) => rules,
+            None => {
+                println!("{}", USAGE);
+                return;
+            }
+        },
+        None => RuleSet::standard(),
+    };
+    println!("Your name: ");
+    let mut name = String::new();
+    io::stdin().read_line(&mut name).unwrap();
+    let bot_match = match BotMatch::new(name.trim(), &rules) {
+        Ok(bot_match) => bot_match,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let mut tui = Tui::new(bot_match);
+    let mut terminal = ratatui::init();
+    let result = tui.play_match(&mut terminal);
+    ratatui::restore();
+    if let Err(e) = result {
+        println!("{}", e);
+    }
+}
+
+pub struct Tui {
+    pub bot_match: BotMatch,
+    opponent: String,
+    // each hand's winner and points, empty for a void hand
+    sheet: Vec<(String, i32)>,
+    // the card picked, counting through the hand as it is laid out
+    selected: usize,
+    message: String,
+}
+
+impl Tui {
+    pub fn new(bot_match: BotMatch) -> Self {
+        let opponent = bot_match.game.view(&bot_match.player).opponent;
+        Tui {
+            bot_match,
+            opponent,
+            sheet: Vec::new(),
+            selected: 0,
+            message: String::new(),
+        }
+    }
+
+    fn play_match(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let match_id = new_match_id();
+        loop {
+            // the game prints as it deals and scores, which has to be wiped off the screen
+            terminal.clear()?;
+            while self.bot_match.game.phase == Phase::Draw
+                || self.bot_match.game.phase == Phase::Discard
+            {
+                terminal.draw(|frame| self.draw(frame))?;
+                if self.bot_match.bot_to_play() {
+                    thread::sleep(BOT_PAUSE);
+                    let action = self.bot_match.game.bot_action();
+                    self.play(action);
+                } else if !self.key(read_key()?) {
+                    return Ok(());
+                }
+            }
+            self.finish_hand(match_id);
+            terminal.clear()?;
+            terminal.draw(|frame| self.draw(frame))?;
+            if read_key()? == KeyCode::Char('q') || self.bot_match.next_hand().is_err() {
+                return Ok(());
+            }
+            self.selected = 0;
+            self.message = format!("new hand, {} goes first", self.bot_match.game.current_turn);
+        }
+    }
+
+    // plays the key pressed on the player's turn, false to quit
+    pub fn key(&mut self, key: KeyCode) -> bool {
+        let hand = arrange(&self.bot_match.game.view(&self.bot_match.player).hand).concat();
+        let action = match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left => {
+                self.selected = self.selected.saturating_sub(1);
+                return true;
+            }
+            KeyCode::Right => {
+                self.selected = (self.selected + 1).min(hand.len().saturating_sub(1));
+                return true;
+            }
+            KeyCode::Char('s') => Action::DrawStock,
+            KeyCode::Char('d') => Action::DrawDiscard,
+            KeyCode::Char('k') => Action::Knock,
+            KeyCode::Char('g') => Action::Gin,
+            KeyCode::Enter | KeyCode::Char(' ') => match hand.get(self.selected) {
+                Some(card) => Action::Discard(card.clone()),
+                None => return true,
+            },
+            _ => return true,
+        };
+        if self.bot_match.game.current_turn == self.bot_match.player {
+            self.play(action);
+        }
+        true
+    }
+
+    fn play(&mut self, action: Action) {
+        let player_move = Move {
+            player: self.bot_match.game.current_turn.clone(),
+            action,
+        };
+        match self.bot_match.game.check_rules(&player_move) {
+            Ok(()) => {
+                let style = CardStyle {
+                    colour: false,
+                    ..CardStyle::current()
+                };
+                self.message = describe_move(&player_move, style);
+                self.bot_match.game.apply_move(player_move);
+            }
+            Err(e) => self.message = e,
+        }
+        let cards = self.bot_match.game.view(&self.bot_match.player).hand.len();
+        self.selected = self.selected.min(cards.saturating_sub(1));
+    }
+
+    // lays the hands down once someone has gone down, then scores the hand and saves it
+    fn finish_hand(&mut self, match_id: u64) {
+        self.bot_match.finish_hand();
+        self.bot_match
+            .game
+            .record(match_id)
+            .save(self.sheet.len() + 1);
+        let winner = self.bot_match.game.score.player.clone();
+        let points = self.bot_match.game.score.points;
+        self.sheet.push((winner.clone(), points));
+
+        let target = self.bot_match.game.rules.target_score;
+        self.message = if self.bot_match.totals[0] >= target {
+            format!(
+                "{} wins the match, press any key to leave",
+                self.bot_match.player
+            )
+        } else if self.bot_match.totals[1] >= target {
+            format!("{} wins the match, press any key to leave", self.opponent)
+        } else if winner.is_empty() {
+            String::from("The stock has run out, the hand is void. Any key deals the next hand")
+        } else {
+            format!(
+                "{} scores {} points. Any key deals the next hand, q quits",
+                winner, points
+            )
+        };
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let view = self.bot_match.game.view(&self.bot_match.player);
+        let [main, sheet] =
+            Layout::horizontal([Constraint::Min(40), Constraint::Length(24)]).areas(frame.area());
+        let [opponent, table, hand, status] = Layout::vertical([
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Min(10),
+            Constraint::Length(4),
+        ])
+        .areas(main);
+
+        // the opponent's hand stays face down until it is laid down
+        let mut lines = vec![Line::from(format!("{} cards in hand", view.opponent_cards))];
+        if !view.opponent_pickups.is_empty() {
+            let style = CardStyle::current();
+            let pickups: Vec<String> = view
+                .opponent_pickups
+                .iter()
+                .map(|card| card.face(style))
+                .collect();
+            lines.push(Line::from(format!(
+                "picked up from the discard pile: {}",
+                pickups.join(" ")
+            )));
+        }
+        let mut laid_down = view.opponent_melds.clone();
+        let mut title = view.opponent.clone();
+        if let Some(deadwood) = &view.opponent_deadwood {
+            title = format!("{} - deadwood {}", view.opponent, meld::deadwood(deadwood));
+            laid_down.push(deadwood.clone());
+        }
+        lines.extend(card_rows(&laid_down, None));
+        frame.render_widget(Paragraph::new(lines).block(titled(&title)), opponent);
+
+        let mut lines = vec![Line::from(format!(
+            "discard pile     stock: {} cards",
+            view.stock
+        ))];
+        let top = view
+            .discard_pile
+            .last()
+            .map(|card| card_spans(Some(card), Style::default()));
+        for (row, stock) in card_spans(None, Style::default()).into_iter().enumerate() {
+            let mut spans = match &top {
+                Some(top) => vec![top[row].clone()],
+                None => vec![Span::raw("     ")],
+            };
+            spans.push(Span::raw("            "));
+            spans.push(stock);
+            lines.push(Line::from(spans));
+        }
+        frame.render_widget(Paragraph::new(lines).block(titled("Table")), table);
+
+        let groups = arrange(&view.hand);
+        let deadwood = meld::deadwood(&meld::best_melds(&view.hand).1);
+        let turn =
+            view.turn == view.player && (view.phase == Phase::Draw || view.phase == Phase::Discard);
+        let mut lines = card_rows(&groups, if turn { Some(self.selected) } else { None });
+        if !view.melds.is_empty() {
+            lines.push(Line::from("laid down"));
+            lines.extend(card_rows(&view.melds, None));
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(titled(&format!("{} - deadwood {}", view.player, deadwood))),
+            hand,
+        );
+
+        let prompt = match view.phase {
+            Phase::Draw if turn => "your turn: draw from the stock (s) or the discard pile (d)",
+            Phase::Discard if turn => "your turn: discard (enter), or knock (k) or gin (g) first",
+            Phase::Draw | Phase::Discard => "waiting for your opponent...",
+            _ => "",
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(self.message.clone()),
+                Line::from(prompt),
+                Line::from(Span::styled(KEYS, Style::default().fg(Color::DarkGray))),
+            ])
+            .block(Block::default().borders(Borders::TOP)),
+            status,
+        );
+
+        let mut lines = vec![Line::from(format!(
+            "{} rules, to {}",
+            self.bot_match.game.rules.name, self.bot_match.game.rules.target_score
+        ))];
+        lines.push(Line::default());
+        for (hand, (winner, points)) in self.sheet.iter().enumerate() {
+            if winner.is_empty() {
+                lines.push(Line::from(format!("{:>2}. void", hand + 1)));
+            } else {
+                lines.push(Line::from(format!(
+                    "{:>2}. {} +{}",
+                    hand + 1,
+                    winner,
+                    points
+                )));
+            }
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(format!(
+            "{} {}",
+            self.bot_match.player, self.bot_match.totals[0]
+        )));
+        lines.push(Line::from(format!(
+            "{} {}",
+            self.opponent, self.bot_match.totals[1]
+        )));
+        frame.render_widget(Paragraph::new(lines).block(titled("Score")), sheet);
+    }
+}
+
+fn titled(title: &str) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .title(String::from(title))
+}
+
+// waits for a key to be pressed, not released
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(key.code);
+            }
+        }
+    }
+}
+
+// the hand as the melds that leave the least deadwood, then the deadwood by suit and rank
+fn arrange(hand: &[Card]) -> Vec<Vec<Card>> {
+    let (mut groups, mut deadwood) = meld::best_melds(hand);
+    deadwood.sort_by_key(|card| {
+        let suit = SUITS.iter().position(|suit| *suit == card.suit);
+        (suit, *RANK_VALUES.get(&card.rank).unwrap())
+    });
+    if !deadwood.is_empty() {
+        groups.push(deadwood);
+    }
+    groups
+}
+
+// a card's three rows of text, face down if there is no card; red suits are drawn in red
+fn card_spans(card: Option<&Card>, style: Style) -> [Span<'static>; 3] {
+    let cards = CardStyle::current();
+    let (face, red) = match card {
+        Some(card) => (card.face(cards), cards.colour && card.is_red()),
+        None => (String::from("###"), false),
+    };
+    let face_style = if red { style.fg(Color::Red) } else { style };
+    [
+        Span::styled("┌───┐", style),
+        Span::styled(format!("│{:<3}│", face), face_style),
+        Span::styled("└───┘", style),
+    ]
+}
+
+// three rows of text drawing the cards side by side, with a gap between groups
+fn card_rows(groups: &[Vec<Card>], selected: Option<usize>) -> Vec<Line<'static>> {
+    let mut rows: [Vec<Span>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+    let mut index = 0;
+    for group in groups.iter() {
+        for card in group.iter() {
+            let style = if selected == Some(index) {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            for (row, span) in rows.iter_mut().zip(card_spans(Some(card), style)) {
+                row.push(span);
+            }
+            index += 1;
+        }
+        for row in rows.iter_mut() {
+            row.push(Span::raw("  "));
+        }
+    }
+    if index == 0 {
+        return Vec::new();
+    }
+    rows.into_iter().map(Line::from).collect()
+}
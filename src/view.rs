@@ -0,0 +1,137 @@
+// What one player can see of a game: their own hand, the melds on the table, the discard pile,
+// how many cards are left in the stock and which cards they have seen the opponent pick up.
+// The opponent's hand and the order of the stock are never part of it.
+
+use crate::cards::{show_cards, CardStyle};
+use crate::hand::{display_hand, HandOrder};
+use crate::{Action, Card, GinGame, Phase};
+
+#[derive(Clone)]
+pub struct PlayerView {
+    pub player: String,
+    pub opponent: String,
+    pub turn: String,
+    pub phase: Phase,
+    pub hand: Vec<Card>,
+    // how the player likes their hand laid out
+    pub order: HandOrder,
+    // melds only exist once they have been laid down, so both players' are shown
+    pub melds: Vec<Vec<Card>>,
+    pub opponent_melds: Vec<Vec<Card>>,
+    pub opponent_cards: usize,
+    // the opponent's deadwood, once their hand has been laid down
+    pub opponent_deadwood: Option<Vec<Card>>,
+    // bottom card first
+    pub discard_pile: Vec<Card>,
+    pub stock: usize,
+    // cards the opponent took from the discard pile and still holds
+    pub opponent_pickups: Vec<Card>,
+}
+
+impl GinGame {
+    pub fn view(&self, player_name: &str) -> PlayerView {
+        let (player, opponent) = if player_name == self.first_player.name {
+            (&self.first_player, &self.second_player)
+        } else {
+            (&self.second_player, &self.first_player)
+        };
+        let laid_down = self.phase == Phase::Over && (self.knock_status || self.gin_status);
+        let mut discard_pile = self.discard_pile.cards.clone();
+        // the knocking discard is face down until the hands are laid down
+        let knocked = self
+            .moves
+            .iter()
+            .any(|m| m.player == player.name && matches!(m.action, Action::Knock | Action::Gin));
+        if self.phase == Phase::Showdown && !knocked {
+            discard_pile.pop();
+        }
+
+        PlayerView {
+            player: player.name.clone(),
+            opponent: opponent.name.clone(),
+            turn: self.current_turn.clone(),
+            phase: self.phase,
+            hand: player.hand.clone(),
+            order: player.order.clone(),
+            melds: player.melds.collection.clone(),
+            opponent_melds: opponent.melds.collection.clone(),
+            opponent_cards: opponent.hand.len(),
+            opponent_deadwood: if laid_down {
+                Some(opponent.hand.clone())
+            } else {
+                None
+            },
+            discard_pile,
+            stock: self.deck.cards.len(),
+            opponent_pickups: self.pickups(&opponent.name),
+        }
+    }
+
+    // follows the discard pile through the hand to see what the player took from it
+    fn pickups(&self, player_name: &str) -> Vec<Card> {
+        let mut pickups: Vec<Card> = Vec::new();
+        // the upcard is the card dealt after both hands
+        let mut pile: Vec<Card> = match self.dealt_deck.len().checked_sub(21) {
+            Some(upcard) => vec![self.dealt_deck[upcard].clone()],
+            None => Vec::new(),
+        };
+        for player_move in self.moves.iter() {
+            let own = player_move.player == player_name;
+            match &player_move.action {
+                Action::DrawDiscard => {
+                    if let Some(card) = pile.pop() {
+                        if own {
+                            pickups.push(card);
+                        }
+                    }
+                }
+                Action::Discard(card) => {
+                    pile.push(card.clone());
+                    pickups.retain(|pickup| pickup != card);
+                }
+                Action::Meld(card, _) | Action::LayOff(card, _) if own => {
+                    pickups.retain(|pickup| pickup != card);
+                }
+                _ => {}
+            }
+        }
+        pickups
+    }
+}
+
+impl PlayerView {
+    pub fn display(&self) {
+        display_hand(&self.player, &self.hand, &self.order);
+        let style = CardStyle::current();
+        display_melds(&self.player, &self.melds);
+
+        println!("{} holds {} cards", self.opponent, self.opponent_cards);
+        for card in self.opponent_pickups.iter() {
+            println!("including the {} from the discard pile", card.show(style));
+        }
+        println!(" ");
+        display_melds(&self.opponent, &self.opponent_melds);
+        if let Some(deadwood) = &self.opponent_deadwood {
+            println!("{}'s deadwood: {}", self.opponent, show_cards(deadwood));
+            println!(" ");
+        }
+
+        match self.discard_pile.last() {
+            Some(card) => println!("Top card of discard pile: {}", card.show(style)),
+            None => println!("Discard pile is empty!"),
+        }
+        println!("Cards left in stock: {}", self.stock);
+        println!(" ");
+    }
+}
+
+fn display_melds(name: &str, melds: &[Vec<Card>]) {
+    if melds.is_empty() {
+        return;
+    }
+    println!("{}'s melds: ", name);
+    for (meld_index, meld) in melds.iter().enumerate() {
+        println!("Meld {}: {}", meld_index, show_cards(meld));
+    }
+    println!(" ");
+}
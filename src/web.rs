@@ -0,0 +1,137 @@
+// The engine for the page in web/, which plays a match against the bot in the browser with no
+// server: the library is built for wasm32 and wasm-bindgen makes WebGame a JavaScript class (see
+// the README). Views and actions are the JSON of the WebSocket protocol in docs/websocket.md,
+// passed as strings: a view is a state message with the match totals and the hand's result
+// added, and an action has the action and card fields of a play message.
+
+use serde_json::{json, Value};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::bot::{BotMatch, BOT_PAUSE};
+use crate::protocol::{action_json, json_action, ServerMessage};
+use crate::{Action, GinGame, Move, Phase, RuleSet};
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct WebGame {
+    bot_match: BotMatch,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl WebGame {
+    // a match against the bot under the named rules, with the first hand dealt
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new(player: &str, rules: &str) -> Result<WebGame, String> {
+        let rules = match RuleSet::named(rules) {
+            Some(rules) => rules,
+            None => return Err(format!("unknown rules: {}", rules)),
+        };
+        Ok(WebGame {
+            bot_match: BotMatch::new(player, &rules)?,
+        })
+    }
+
+    // how long the page waits before each of the bot's moves, in milliseconds
+    pub fn bot_pause() -> u32 {
+        BOT_PAUSE.as_millis() as u32
+    }
+
+    // what the player can see, with the totals and, once the hand is over, who won it
+    pub fn view(&self) -> String {
+        let game = &self.bot_match.game;
+        let mut view = ServerMessage::View(game.view(&self.bot_match.player)).to_json();
+        view["totals"] = json!(self.bot_match.totals);
+        view["target"] = json!(game.rules.target_score);
+        view["result"] = if self.bot_match.scored {
+            let winner = &game.score.player;
+            json!({
+                "winner": if winner.is_empty() { None } else { Some(winner) },
+                "points": game.score.points,
+            })
+        } else {
+            Value::Null
+        };
+        view.to_string()
+    }
+
+    // the moves the player can make now, none on the bot's turn
+    pub fn legal_actions(&self) -> String {
+        let game = &self.bot_match.game;
+        let actions: Vec<Value> = if game.current_turn == self.bot_match.player {
+            game.legal_actions().iter().map(action_json).collect()
+        } else {
+            Vec::new()
+        };
+        json!(actions).to_string()
+    }
+
+    // plays the player's move, e.g. {"action": "discard", "card": "Qh"}
+    pub fn apply_action(&mut self, action: &str) -> Result<(), String> {
+        let message: Value = match serde_json::from_str(action) {
+            Ok(message) => message,
+            Err(_) => return Err(format!("invalid action: {}", action)),
+        };
+        let action = json_action(&message)?;
+        self.play(Move {
+            player: self.bot_match.player.clone(),
+            action,
+        })
+    }
+
+    // plays the bot's move and returns it, or nothing when it is not the bot's turn
+    pub fn bot_action(&mut self) -> Option<String> {
+        if !self.bot_match.bot_to_play() {
+            return None;
+        }
+        let action = self.bot_match.game.bot_action();
+        let json = action_json(&action).to_string();
+        let player = self.bot_match.game.current_turn.clone();
+        self.play(Move { player, action }).ok()?;
+        Some(json)
+    }
+
+    // deals the next hand once this one is over, unless the match is
+    pub fn next_hand(&mut self) -> Result<(), String> {
+        self.bot_match.next_hand()
+    }
+
+    pub fn match_over(&self) -> bool {
+        self.bot_match.match_over()
+    }
+}
+
+impl WebGame {
+    // a move by either player, scoring the hand once someone goes down
+    fn play(&mut self, player_move: Move) -> Result<(), String> {
+        self.bot_match.game.check_rules(&player_move)?;
+        self.bot_match.game.apply_move(player_move);
+        self.bot_match.finish_hand();
+        Ok(())
+    }
+}
+
+impl GinGame {
+    // every move the player whose turn it is could make
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let candidates = match self.phase {
+            Phase::Draw => vec![Action::DrawStock, Action::DrawDiscard],
+            Phase::Discard => {
+                let mut actions = vec![Action::Knock, Action::Gin];
+                let hand = &self.current_player().hand;
+                actions.extend(hand.iter().cloned().map(Action::Discard));
+                actions
+            }
+            Phase::Showdown | Phase::Over => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|action| {
+                let player_move = Move {
+                    player: self.current_turn.clone(),
+                    action: action.clone(),
+                };
+                self.check_rules(&player_move).is_ok()
+            })
+            .collect()
+    }
+}
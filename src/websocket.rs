@@ -0,0 +1,72 @@
+// Carries the server's messages to browsers as JSON text frames over a WebSocket. The message
+// schema is in docs/websocket.md.
+
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Error, Message};
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::server::{Connection, Hangup};
+
+// how long a read waits before checking for messages to send
+const POLL: Duration = Duration::from_millis(20);
+
+pub fn connection(stream: TcpStream) -> Result<Connection, String> {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => return Err(format!("WebSocket handshake failed: {}", e)),
+    };
+    if let Err(e) = socket.get_ref().set_read_timeout(Some(POLL)) {
+        return Err(e.to_string());
+    }
+
+    let (sender, outgoing) = channel::<ServerMessage>();
+    let (incoming, receiver) = channel();
+    // reading and writing share the socket, so one thread does both
+    thread::spawn(move || {
+        let incoming = Hangup(incoming);
+        loop {
+            match outgoing.try_recv() {
+                Ok(message) => {
+                    let text = message.to_json().to_string();
+                    if socket.send(Message::text(text)).is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.flush();
+                    break;
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    if incoming
+                        .0
+                        .send(Some(ClientMessage::from_json(&text)))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                Ok(Message::Close(_)) => break,
+                Ok(_) => {}
+                Err(Error::Io(e))
+                    if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+                Err(_) => break,
+            }
+        }
+    });
+    Ok(Connection {
+        sender,
+        receiver,
+        features: Vec::new(),
+        account: None,
+    })
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions, PROTOCOL_VERSION};
+use gin::server::{self, Lobby, ServerOptions};
+use serde_json::{json, Value};
+use tungstenite::{connect, Message};
+
+// a server keeping its accounts in a fresh file for the test
+fn server(test: &str) -> (String, String) {
+    let path: PathBuf = std::env::temp_dir().join(format!("gin-{}-{}.txt", test, process::id()));
+    let _ = fs::remove_file(&path);
+    let path = path.to_string_lossy().into_owned();
+    (start(&path), path)
+}
+
+fn start(path: &str) -> String {
+    start_server(Lobby::create(ServerOptions {
+        accounts: Some(String::from(path)),
+        ..ServerOptions::standard()
+    }))
+}
+
+fn request(address: &str, message: ClientMessage) -> Result<(String, String), String> {
+    let mut client = Client::open(address, "");
+    client.send(&message);
+    client.wait_for(|message| match message {
+        ServerMessage::Account(name, token) => Some(Ok((name, token))),
+        ServerMessage::Error(text) => Some(Err(text)),
+        _ => None,
+    })
+}
+
+fn register(name: &str, password: &str) -> ClientMessage {
+    ClientMessage::Register(String::from(name), String::from(password))
+}
+
+fn login(name: &str, password: &str) -> ClientMessage {
+    ClientMessage::Login(String::from(name), String::from(password))
+}
+
+#[test]
+fn players_register_log_in_and_use_their_tokens() {
+    let (address, path) = server("register");
+
+    let (name, token) = request(&address, register("Alice", "correct horse")).unwrap();
+    assert_eq!(name, "Alice");
+    assert!(request(&address, register("Alice", "another one"))
+        .unwrap_err()
+        .contains("taken"));
+    assert!(request(&address, register("Bob", "short"))
+        .unwrap_err()
+        .contains("at least"));
+    assert!(
+        request(&address, ClientMessage::Name(String::from("Alice")))
+            .unwrap_err()
+            .contains("log in")
+    );
+
+    assert!(request(&address, login("Alice", "wrong horse")).is_err());
+    assert!(request(&address, login("Nobody", "correct horse")).is_err());
+    let (_, second_token) = request(&address, login("Alice", "correct horse")).unwrap();
+    assert_ne!(second_token, token);
+    let (name, _) = request(&address, ClientMessage::Authenticate(token.clone())).unwrap();
+    assert_eq!(name, "Alice");
+
+    // the password is only kept hashed, and survives a restart while tokens do not
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("Alice\t$argon2"));
+    assert!(!saved.contains("correct horse"));
+    let restarted = start(&path);
+    assert!(request(&restarted, login("Alice", "correct horse")).is_ok());
+    assert!(request(&restarted, ClientMessage::Authenticate(token)).is_err());
+}
+
+#[test]
+fn players_sit_down_as_their_accounts() {
+    let (address, _) = server("seats");
+    request(&address, register("Alice", "correct horse")).unwrap();
+    request(&address, register("Bob", "battery staple")).unwrap();
+
+    let mut alice = Client::open(&address, "Alice");
+    alice.send(&login("Alice", "correct horse"));
+    alice.send(&ClientMessage::Create(TableOptions::standard()));
+    let table = alice.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    let mut bob = Client::open(&address, "Bob");
+    bob.send(&login("Bob", "battery staple"));
+    bob.send(&ClientMessage::Join(Some(table)));
+
+    let view = bob.view();
+    assert_eq!(view.player, "Bob");
+    assert_eq!(view.opponent, "Alice");
+}
+
+#[test]
+fn names_with_control_characters_cannot_be_registered() {
+    // JSON can carry a newline in a name where the line protocol cannot
+    let path: PathBuf = std::env::temp_dir().join(format!("gin-newline-{}.txt", process::id()));
+    let _ = fs::remove_file(&path);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let lobby = Lobby::create(ServerOptions {
+        accounts: Some(path.to_string_lossy().into_owned()),
+        ..ServerOptions::standard()
+    });
+    thread::spawn(move || server::serve_websockets(listener, lobby));
+
+    let (mut client, _) = connect(format!("ws://{}", address)).unwrap();
+    let mut send = |message: Value| client.send(Message::text(message.to_string())).unwrap();
+    send(json!({"type": "hello", "version": PROTOCOL_VERSION, "features": ["accounts"]}));
+    for name in ["x\nVictim", "tab\there", &"a".repeat(33)] {
+        send(json!({"type": "register", "name": name, "password": "correct horse"}));
+    }
+    let mut errors = 0;
+    while errors < 3 {
+        if let Message::Text(text) = client.read().unwrap() {
+            let message: Value = serde_json::from_str(&text).unwrap();
+            assert_ne!(message["type"], "account");
+            if message["type"] == "error" {
+                assert_eq!(message["text"], "choose another name");
+                errors += 1;
+            }
+        }
+    }
+    assert!(fs::read_to_string(&path).unwrap_or_default().is_empty());
+}
@@ -0,0 +1,100 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use gin::api;
+use serde_json::Value;
+
+// a match between Ada and Grace under the Quick rules, which Grace won 63 to 0 over two hands
+const HANDS: &str = "tests/hands";
+
+fn start_api() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || api::serve(listener, String::from(HANDS)));
+    address
+}
+
+fn request(address: &str, method: &str, target: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+        method, target, address
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn matches_and_their_hands_are_listed() {
+    let address = start_api();
+    let (status, matches) = request(&address, "GET", "/matches?player=Grace");
+    assert_eq!(status, 200);
+    assert_eq!(matches.as_array().unwrap().len(), 1);
+    assert_eq!(matches[0]["match"], 42);
+    assert_eq!(matches[0]["winner"], "Grace");
+    assert_eq!(matches[0]["hands"], 2);
+    let (_, matches) = request(&address, "GET", "/matches?rules=Standard");
+    assert!(matches.as_array().unwrap().is_empty());
+
+    let (status, found) = request(&address, "GET", "/matches/42");
+    assert_eq!(status, 200);
+    let hands = found["hands"].as_array().unwrap();
+    assert_eq!(hands.len(), 2);
+    let points: i64 = hands
+        .iter()
+        .map(|hand| hand["points"].as_i64().unwrap())
+        .sum();
+    assert_eq!(points, 63);
+}
+
+#[test]
+fn a_hand_is_replayed_move_by_move() {
+    let address = start_api();
+    let (status, replay) = request(&address, "GET", "/matches/42/hands/1");
+    assert_eq!(status, 200);
+    let steps = replay["steps"].as_array().unwrap();
+    assert_eq!(steps[0]["move"], Value::Null);
+    assert_eq!(steps[0]["players"][0]["hand"].as_array().unwrap().len(), 10);
+    assert_eq!(steps[0]["stock"], 31);
+    assert_eq!(steps[1]["move"]["player"], "Ada");
+    assert_eq!(steps[1]["move"]["action"], "draw-discard");
+    assert_eq!(steps[1]["players"][0]["hand"].as_array().unwrap().len(), 11);
+    assert!(replay["notation"].as_str().unwrap().contains("Grace"));
+
+    let (status, _) = request(&address, "GET", "/matches/42/hands/3");
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn players_have_stats_ratings_and_a_place_on_the_leaderboard() {
+    let address = start_api();
+    let (status, grace) = request(&address, "GET", "/players/Grace");
+    assert_eq!(status, 200);
+    assert_eq!(grace["matches_won"], 1);
+    assert_eq!(grace["hands_won"], 2);
+    assert_eq!(grace["ratings"][0]["rules"], "Quick");
+    assert!(grace["ratings"][0]["rating"].as_f64().unwrap() > 1500.0);
+
+    let (_, history) = request(&address, "GET", "/players/Ada/ratings?rules=Quick");
+    assert_eq!(history["history"].as_array().unwrap().len(), 1);
+    let (status, _) = request(&address, "GET", "/players/Ada/ratings");
+    assert_eq!(status, 404);
+
+    let (_, leaderboard) = request(&address, "GET", "/leaderboard?rules=Quick");
+    assert_eq!(leaderboard["players"][0]["name"], "Grace");
+    assert_eq!(leaderboard["players"][1]["name"], "Ada");
+}
+
+#[test]
+fn unknown_requests_are_refused() {
+    let address = start_api();
+    assert_eq!(request(&address, "GET", "/players/Nobody").0, 404);
+    assert_eq!(request(&address, "GET", "/tables").0, 404);
+    assert_eq!(request(&address, "POST", "/matches").0, 405);
+}
@@ -0,0 +1,38 @@
+use gin::cards::CardStyle;
+use gin::record::HandRecord;
+
+const SYMBOLS: CardStyle = CardStyle {
+    symbols: true,
+    colour: true,
+};
+
+const ASCII: CardStyle = CardStyle {
+    symbols: false,
+    colour: false,
+};
+
+// Grace's hand as dealt in the recorded match
+fn faces(style: CardStyle) -> Vec<String> {
+    let record = HandRecord::load("tests/hands/42-1.log").unwrap();
+    let view = record.play_to(0).unwrap().view("Grace");
+    view.hand.iter().map(|card| card.show(style)).collect()
+}
+
+#[test]
+fn cards_show_their_rank_and_suit_symbol() {
+    let faces = faces(SYMBOLS);
+    assert_eq!(faces.len(), 10);
+    assert!(faces.contains(&String::from("A♣")));
+    assert!(faces.contains(&String::from("3♠")));
+    // red suits are coloured
+    assert!(faces.contains(&String::from("\x1b[31m6♥\x1b[0m")));
+    assert!(faces.contains(&String::from("\x1b[31mK♦\x1b[0m")));
+}
+
+#[test]
+fn ascii_cards_are_their_codes() {
+    let faces = faces(ASCII);
+    assert!(faces.contains(&String::from("Ac")));
+    assert!(faces.contains(&String::from("6h")));
+    assert!(faces.iter().all(|face| face.is_ascii()));
+}
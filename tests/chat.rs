@@ -0,0 +1,101 @@
+mod common;
+
+use std::fs;
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions};
+use gin::record::{ChatLine, HandRecord};
+use gin::server::Lobby;
+
+fn seat_two() -> (Client, Client) {
+    let address = start_server(Lobby::shared());
+    let mut alice = Client::connect(&address, "Alice");
+    alice.send(&ClientMessage::Create(TableOptions::standard()));
+    let table = alice.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    let mut bob = Client::connect(&address, "Bob");
+    bob.send(&ClientMessage::Join(Some(table)));
+    alice.opponent();
+    bob.opponent();
+    (alice, bob)
+}
+
+fn said(client: &mut Client) -> (String, String) {
+    client.wait_for(|message| match message {
+        ServerMessage::Chat(player, text) => Some((player, text)),
+        ServerMessage::Emote(player, emote) => Some((player, format!("*{}*", emote))),
+        _ => None,
+    })
+}
+
+#[test]
+fn players_chat_and_send_emotes_unless_muted() {
+    let (mut alice, mut bob) = seat_two();
+
+    alice.send(&ClientMessage::Chat(String::from("good luck")));
+    assert_eq!(
+        said(&mut bob),
+        (String::from("Alice"), String::from("good luck"))
+    );
+    bob.send(&ClientMessage::Emote(String::from("thanks")));
+    assert_eq!(
+        said(&mut alice),
+        (String::from("Alice"), String::from("good luck"))
+    );
+    assert_eq!(
+        said(&mut alice),
+        (String::from("Bob"), String::from("*thanks*"))
+    );
+
+    bob.send(&ClientMessage::Emote(String::from("shrug")));
+    assert!(bob.error().contains("no emote called shrug"));
+
+    bob.send(&ClientMessage::Mute(true));
+    bob.wait_for_info("Alice's chat is muted");
+    alice.send(&ClientMessage::Chat(String::from("are you there")));
+    said(&mut alice);
+    bob.send(&ClientMessage::Mute(false));
+    bob.wait_for_info("no longer muted");
+    alice.send(&ClientMessage::Chat(String::from("hello again")));
+    said(&mut alice);
+    assert_eq!(said(&mut bob).1, "hello again");
+}
+
+#[test]
+fn chatting_too_fast_is_refused() {
+    let (mut alice, mut bob) = seat_two();
+    for index in 0..6 {
+        alice.send(&ClientMessage::Chat(format!("message {}", index)));
+    }
+    assert!(alice.error().contains("too fast"));
+    for index in 0..5 {
+        assert_eq!(said(&mut bob).1, format!("message {}", index));
+    }
+}
+
+#[test]
+fn chat_is_kept_among_the_moves_of_a_saved_hand() {
+    let log = fs::read_to_string("tests/hands/42-1.log").unwrap();
+    let mut record = HandRecord::from_log(&log).unwrap();
+    record.chat = vec![
+        ChatLine {
+            after: 0,
+            player: String::from("Grace"),
+            text: String::from("good luck: have fun"),
+            emote: false,
+        },
+        ChatLine {
+            after: 2,
+            player: String::from("Ada"),
+            text: String::from("oops"),
+            emote: true,
+        },
+    ];
+    let log = record.to_log();
+    assert!(log.contains("move Ada: discard Ks\nemote Ada: oops\nmove Grace: draw-stock\n"));
+    let reloaded = HandRecord::from_log(&log).unwrap();
+    assert_eq!(reloaded.chat, record.chat);
+    assert_eq!(reloaded.moves.len(), record.moves.len());
+}
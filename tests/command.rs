@@ -0,0 +1,146 @@
+use gin::command::{parse, parse_card, Command};
+use gin::protocol::ClientMessage;
+use gin::record::HandRecord;
+use gin::{Deck, GinGame, Phase};
+
+fn parsed(line: &str) -> Command {
+    parse(line).unwrap()
+}
+
+// the recorded hand after the given number of moves
+fn hand_at(step: usize) -> GinGame {
+    HandRecord::load("tests/hands/42-1.log")
+        .unwrap()
+        .play_to(step)
+        .unwrap()
+}
+
+#[test]
+fn cards_can_be_written_as_codes_symbols_or_words() {
+    let queen = parse_card("Qh").unwrap();
+    for text in ["qh", "QH", "Q♥", "q♡", "queen of hearts", "Queen of Heart"] {
+        assert_eq!(parse_card(text).unwrap(), queen, "{}", text);
+    }
+    assert_eq!(
+        parse_card("10c").unwrap(),
+        parse_card("ten of clubs").unwrap()
+    );
+    assert_eq!(parse_card("Tc").unwrap(), parse_card("10♣").unwrap());
+    assert!(parse_card("queen of horses")
+        .unwrap_err()
+        .contains("not a card"));
+    assert!(parse_card("1h").is_err());
+}
+
+#[test]
+fn cards_ending_in_other_characters_are_refused() {
+    // suits that take more than one byte, and letters that only look like one
+    for text in ["7é", "Q€", "Kх", "é", "€€"] {
+        assert!(parse_card(text).is_err(), "{}", text);
+    }
+    assert!(Deck::create_from("Q♥ K♥").is_err());
+    assert!(ClientMessage::from_line("play\tdiscard Q€").is_err());
+}
+
+#[test]
+fn commands_are_parsed_with_helpful_errors() {
+    assert_eq!(parsed("draw"), Command::DrawStock);
+    assert_eq!(parsed("Draw Stock"), Command::DrawStock);
+    assert_eq!(parsed("take"), Command::Take);
+    assert_eq!(
+        parsed("discard QH"),
+        Command::Discard(parse_card("Qh").unwrap())
+    );
+    assert_eq!(
+        parsed("knock 7c"),
+        Command::Knock(Some(parse_card("7c").unwrap()))
+    );
+    assert_eq!(parsed("gin"), Command::Gin(None));
+    assert_eq!(
+        parsed("meld 3h, 4h and five of hearts"),
+        Command::Meld(vec![
+            parse_card("3h").unwrap(),
+            parse_card("4h").unwrap(),
+            parse_card("5h").unwrap(),
+        ])
+    );
+    assert_eq!(
+        parsed("lay off 9s"),
+        Command::LayOff(parse_card("9s").unwrap())
+    );
+    assert_eq!(parsed("undo"), Command::Undo);
+    assert_eq!(parsed("help"), Command::Help);
+
+    assert!(parse("discard").unwrap_err().contains("which card"));
+    assert!(parse("discard qh 3c").unwrap_err().contains("one card"));
+    assert!(parse("meld 3h 4h").unwrap_err().contains("three cards"));
+    assert!(parse("d-3").unwrap_err().contains("not a command"));
+    // melds are no longer limited to ten
+    assert!(parse("meld 3h 4h 5h 6h 7h 8h 9h 10h jh qh kh").is_ok());
+}
+
+#[test]
+fn a_knock_with_a_discard_that_is_refused_is_taken_back() {
+    // Grace to draw, holding two sets and the ace of clubs and spades
+    let mut game = hand_at(2);
+    assert!(game
+        .turn_command(&parsed("knock"))
+        .unwrap_err()
+        .contains("draw first"));
+    game.turn_command(&parsed("take")).unwrap();
+    assert!(game
+        .turn_command(&parsed("discard ks"))
+        .unwrap_err()
+        .contains("just picked up"));
+    assert!(game
+        .turn_command(&parsed("knock 5h"))
+        .unwrap_err()
+        .contains("deadwood"));
+    // nothing was left half done
+    assert!(game.turn_command(&parsed("discard kd")).is_ok());
+    let view = game.view("Grace");
+    assert_eq!(view.turn, "Ada");
+    assert_eq!(view.hand.len(), 10);
+}
+
+#[test]
+fn melds_are_laid_down_checked_and_taken_back() {
+    // Grace has knocked and discarded
+    let mut game = hand_at(13);
+    assert_eq!(game.view("Grace").phase, Phase::Showdown);
+    assert!(game.undo().unwrap_err().contains("discard"));
+
+    assert!(game
+        .lay_meld(&[
+            parse_card("6d").unwrap(),
+            parse_card("6h").unwrap(),
+            parse_card("As").unwrap(),
+        ])
+        .unwrap_err()
+        .contains("not a set or a run"));
+    assert!(game
+        .lay_meld(&[
+            parse_card("Kh").unwrap(),
+            parse_card("Qh").unwrap(),
+            parse_card("Jh").unwrap(),
+        ])
+        .unwrap_err()
+        .contains("do not hold"));
+    game.lay_meld(&[
+        parse_card("3s").unwrap(),
+        parse_card("3c").unwrap(),
+        parse_card("3d").unwrap(),
+    ])
+    .unwrap();
+    assert_eq!(game.view("Grace").melds.len(), 1);
+    assert_eq!(game.view("Grace").hand.len(), 7);
+    assert!(game
+        .lay_off(&parse_card("6d").unwrap())
+        .unwrap_err()
+        .contains("did not go down"));
+
+    game.undo().unwrap();
+    let view = game.view("Grace");
+    assert!(view.melds.is_empty());
+    assert_eq!(view.hand.len(), 10);
+}
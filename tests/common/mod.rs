@@ -0,0 +1,146 @@
+// not every test uses every helper
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use gin::protocol::{self, ClientMessage, ServerMessage, PROTOCOL_VERSION};
+use gin::server::{self, Lobby};
+use gin::view::PlayerView;
+use gin::{Action, Phase};
+
+// a `gin connect` client speaking the line protocol
+pub struct Client {
+    pub name: String,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    pub fn connect(address: &str, name: &str) -> Client {
+        let mut client = Client::open(address, name);
+        client.send(&ClientMessage::Name(String::from(name)));
+        client
+    }
+
+    // takes back a seat with the session from an earlier connection
+    pub fn resume(address: &str, name: &str, session: &str) -> Client {
+        let mut client = Client::open(address, name);
+        client.send(&ClientMessage::Resume(String::from(session)));
+        client
+    }
+
+    // a connection that has said hello and nothing more
+    pub fn open(address: &str, name: &str) -> Client {
+        let mut client = Client::unintroduced(address, name);
+        client.send(&ClientMessage::Hello(
+            PROTOCOL_VERSION,
+            protocol::features(),
+        ));
+        client
+    }
+
+    // a connection that has not said hello yet
+    pub fn unintroduced(address: &str, name: &str) -> Client {
+        let stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .unwrap();
+        Client {
+            name: String::from(name),
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+        }
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) {
+        writeln!(self.writer, "{}", message.to_line()).unwrap();
+    }
+
+    pub fn receive(&mut self) -> ServerMessage {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        ServerMessage::from_line(&line).unwrap()
+    }
+
+    // whether the server has hung up, once everything it sent has been read
+    pub fn closed(&mut self) -> bool {
+        let mut line = String::new();
+        matches!(self.reader.read_line(&mut line), Ok(0))
+    }
+
+    // skips messages until one the test is looking for
+    pub fn wait_for<T>(&mut self, wanted: impl Fn(ServerMessage) -> Option<T>) -> T {
+        loop {
+            if let Some(found) = wanted(self.receive()) {
+                return found;
+            }
+        }
+    }
+
+    pub fn session(&mut self) -> String {
+        self.wait_for(|message| match message {
+            ServerMessage::Session(session) => Some(session),
+            _ => None,
+        })
+    }
+
+    // the next error the server sends
+    pub fn error(&mut self) -> String {
+        self.wait_for(|message| match message {
+            ServerMessage::Error(text) => Some(text),
+            _ => None,
+        })
+    }
+
+    // skips messages until an info message containing the text
+    pub fn wait_for_info(&mut self, text: &str) {
+        self.wait_for(|message| match message {
+            ServerMessage::Info(info) if info.contains(text) => Some(()),
+            _ => None,
+        });
+    }
+
+    pub fn view(&mut self) -> PlayerView {
+        self.wait_for(|message| match message {
+            ServerMessage::View(view) => Some(view),
+            _ => None,
+        })
+    }
+
+    // draws from the stock and throws back the last card in hand, once it is this player's turn
+    pub fn play_turn(&mut self) {
+        let name = &self.name.clone();
+        let mine = |phase: Phase| {
+            move |message| match message {
+                ServerMessage::View(view) if view.turn == *name && view.phase == phase => {
+                    Some(view)
+                }
+                _ => None,
+            }
+        };
+        self.wait_for(mine(Phase::Draw));
+        self.send(&ClientMessage::Play(Action::DrawStock));
+        let view = self.wait_for(mine(Phase::Discard));
+        let card = view.hand.last().unwrap().clone();
+        self.send(&ClientMessage::Play(Action::Discard(card)));
+    }
+
+    // the opponent named in the first view of the match
+    pub fn opponent(&mut self) -> String {
+        self.wait_for(|message| match message {
+            ServerMessage::View(view) => Some(view.opponent),
+            _ => None,
+        })
+    }
+}
+
+pub fn start_server(lobby: Arc<Mutex<Lobby>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || server::serve(listener, lobby));
+    address
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use common::{start_server, Client};
+use gin::correspondence::Correspondence;
+use gin::protocol::{ClientMessage, ServerMessage};
+use gin::server::{Lobby, ServerOptions};
+use gin::Action;
+
+const DAY: Duration = Duration::from_secs(86400);
+
+// an empty directory for the test's games
+fn games_dir(test: &str) -> String {
+    let dir: PathBuf = std::env::temp_dir().join(format!("gin-{}-{}", test, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir.to_string_lossy().into_owned()
+}
+
+#[test]
+fn a_game_is_played_over_several_sittings() {
+    let dir = games_dir("sittings");
+    let mut games = Correspondence::load(&dir, None);
+    let id = games.challenge("Alice", "Bob", "Standard", DAY).unwrap();
+    games.accept(id, "Bob").unwrap();
+    let turn = games.games_of("Alice")[0].turn.clone();
+    let waiting = if turn == "Alice" { "Bob" } else { "Alice" };
+    assert!(games.post(id, waiting, Action::DrawStock).is_err());
+    assert!(games.post(id, "Carol", Action::DrawStock).is_err());
+    let (_, drawn) = games.post(id, &turn, Action::DrawStock).unwrap();
+    assert_eq!(drawn.hand.len(), 11);
+
+    // the server restarts between moves
+    let mut games = Correspondence::load(&dir, None);
+    let (_, view) = games.show(id, &turn).unwrap();
+    assert_eq!(view.hand, drawn.hand);
+    assert_eq!(view.discard_pile, drawn.discard_pile);
+    let card = view.hand.last().unwrap().clone();
+    let (text, view) = games.post(id, &turn, Action::Discard(card)).unwrap();
+    assert_eq!(view.hand.len(), 10);
+    assert!(text.contains(&format!("{} to play", waiting)));
+    assert_eq!(games.games_of(waiting)[0].turn, waiting);
+
+    let mail = fs::read_to_string(PathBuf::from(&dir).join("mail.txt")).unwrap();
+    assert!(mail.contains(&format!("To: {}", turn)));
+    assert!(mail.contains(&format!("To: {}", waiting)));
+}
+
+#[test]
+fn a_missed_deadline_forfeits_the_match() {
+    let dir = games_dir("deadline");
+    let mut games = Correspondence::load(&dir, None);
+    let id = games
+        .challenge("Alice", "Bob", "Quick", Duration::ZERO)
+        .unwrap();
+    games.accept(id, "Bob").unwrap();
+    let late = games.games_of("Bob")[0].turn.clone();
+    games.check_deadlines();
+    assert!(games.games_of("Bob")[0].turn.is_empty());
+    assert!(games.post(id, &late, Action::DrawStock).is_err());
+
+    let games = Correspondence::load(&dir, None);
+    let (text, _) = games.show(id, "Alice").unwrap();
+    assert!(text.contains("is over"));
+    let mail = fs::read_to_string(PathBuf::from(&dir).join("mail.txt")).unwrap();
+    assert!(mail.contains(&format!("{} did not move in time", late)));
+}
+
+#[test]
+fn players_are_told_of_their_move_through_the_webhook() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let webhook = format!("http://{}/gin", listener.local_addr().unwrap());
+    let mut games = Correspondence::load(&games_dir("webhook"), Some(webhook));
+    let id = games.challenge("Alice", "Bob", "Standard", DAY).unwrap();
+    let body = receive_notification(&listener);
+    assert_eq!(body["player"], "Bob");
+    assert_eq!(body["game"], 1);
+    assert!(body["text"].as_str().unwrap().contains("accept 1"));
+
+    games.accept(id, "Bob").unwrap();
+    let turn = games.games_of("Alice")[0].turn.clone();
+    let body = receive_notification(&listener);
+    assert_eq!(body["player"], turn.as_str());
+    assert_eq!(body["game"], 1);
+}
+
+// reads one post to the webhook and answers it
+fn receive_notification(listener: &TcpListener) -> serde_json::Value {
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request = String::new();
+    reader.read_line(&mut request).unwrap();
+    assert_eq!(request.trim(), "POST /gin HTTP/1.1");
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+            length = value.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    write!(stream, "HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn clients_challenge_and_list_their_games() {
+    let address = start_server(Lobby::create(ServerOptions {
+        games: games_dir("clients"),
+        ..ServerOptions::standard()
+    }));
+    let mut alice = Client::connect(&address, "Alice");
+    alice.send(&ClientMessage::Challenge(
+        String::from("Bob"),
+        String::from("Oklahoma"),
+        24,
+    ));
+    alice.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+    alice.send(&ClientMessage::Challenge(
+        String::from("Bob"),
+        String::from("Standard"),
+        24,
+    ));
+    assert_eq!(alice.opponent(), "Bob");
+
+    let mut bob = Client::connect(&address, "Bob");
+    bob.send(&ClientMessage::Games);
+    let games = bob.wait_for(|message| match message {
+        ServerMessage::Games(games) => Some(games),
+        _ => None,
+    });
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].opponent, "Alice");
+    assert_eq!(games[0].turn, "Bob");
+    assert_eq!(games[0].deadline, 0);
+    alice.send(&ClientMessage::Accept(games[0].game));
+    alice.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+    bob.send(&ClientMessage::Accept(games[0].game));
+    assert_eq!(bob.view().hand.len(), 10);
+    bob.send(&ClientMessage::Games);
+    let games = bob.wait_for(|message| match message {
+        ServerMessage::Games(games) => Some(games),
+        _ => None,
+    });
+    assert!(games[0].deadline > 0);
+    bob.send(&ClientMessage::Show(games[0].game));
+    assert_eq!(bob.view().hand.len(), 10);
+    bob.send(&ClientMessage::Show(games[0].game + 1));
+    bob.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+
+    let (mover, game) = if games[0].turn == "Bob" {
+        (&mut bob, games[0].game)
+    } else {
+        (&mut alice, games[0].game)
+    };
+    mover.send(&ClientMessage::Post(game, Action::DrawStock));
+    assert_eq!(mover.view().hand.len(), 11);
+}
+
+#[test]
+fn deadlines_are_kept_in_range() {
+    for line in [
+        "challenge\tBob\tQuick\t0",
+        "challenge\tBob\tQuick\t721",
+        "challenge\tBob\tQuick\t18446744073709551615",
+    ] {
+        assert!(ClientMessage::from_line(line).is_err(), "{}", line);
+    }
+    assert!(ClientMessage::from_line("challenge\tBob\tQuick\t720").is_ok());
+    let json = r#"{"type": "challenge", "opponent": "Bob", "hours": 18446744073709551615}"#;
+    assert!(ClientMessage::from_json(json).is_err());
+
+    // a saved game whose deadline is out of range is not loaded
+    let dir = games_dir("range");
+    let mut games = Correspondence::load(&dir, None);
+    games.challenge("Alice", "Bob", "Quick", DAY).unwrap();
+    let path = PathBuf::from(&dir).join("1.game");
+    let saved = fs::read_to_string(&path).unwrap();
+    let saved = saved.replace("deadline 86400", "deadline 18446744073709551615");
+    fs::write(&path, saved).unwrap();
+    assert!(Correspondence::load(&dir, None)
+        .games_of("Alice")
+        .is_empty());
+}
+
+#[test]
+fn a_match_waits_for_the_challenged_player_to_accept() {
+    let dir = games_dir("accept");
+    let mut games = Correspondence::load(&dir, None);
+    for opponent in ["Alice", "", "Bob\nCarol", "Bob\tCarol"] {
+        assert!(games
+            .challenge("Alice", opponent, "Quick", Duration::ZERO)
+            .is_err());
+    }
+    let id = games
+        .challenge("Alice", "Bob", "Quick", Duration::ZERO)
+        .unwrap();
+    let mail = fs::read_to_string(PathBuf::from(&dir).join("mail.txt")).unwrap();
+    assert!(mail.contains("To: Bob"));
+    assert!(!mail.contains("To: Alice"));
+
+    // the clock has not started, so however long it takes nobody forfeits
+    games.check_deadlines();
+    let mut games = Correspondence::load(&dir, None);
+    games.check_deadlines();
+    let summary = &games.games_of("Alice")[0];
+    assert_eq!(summary.turn, "Bob");
+    assert_eq!(summary.deadline, 0);
+    for player in ["Alice", "Bob"] {
+        assert!(games.post(id, player, Action::DrawStock).is_err());
+    }
+    assert!(games.accept(id, "Alice").is_err());
+    assert!(games.accept(id, "Carol").is_err());
+
+    let (text, view) = games.accept(id, "Bob").unwrap();
+    assert!(text.contains("to play by"));
+    assert_eq!(view.hand.len(), 10);
+    assert!(games.accept(id, "Bob").is_err());
+    let turn = games.games_of("Bob")[0].turn.clone();
+    games.post(id, &turn, Action::DrawStock).unwrap();
+}
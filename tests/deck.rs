@@ -0,0 +1,46 @@
+use gin::record::HandRecord;
+use gin::Deck;
+
+// the deck of the recorded match 42, from the top
+fn recorded_order() -> String {
+    HandRecord::load("tests/hands/42-1.log")
+        .unwrap()
+        .deal()
+        .export()
+}
+
+#[test]
+fn a_deck_is_imported_in_any_of_its_forms() {
+    let order = recorded_order();
+    assert!(order.starts_with("3h 3s Tc 3c 9s 6d"));
+    assert_eq!(Deck::create_from(&order).unwrap().export(), order);
+    let with_commas = order.replace(' ', ", ");
+    assert_eq!(Deck::create_from(&with_commas).unwrap().export(), order);
+    let run_together = order.replace(' ', "");
+    assert_eq!(Deck::create_from(&run_together).unwrap().export(), order);
+}
+
+#[test]
+fn a_deck_that_is_not_a_full_pack_is_refused() {
+    let order = recorded_order();
+    let codes: Vec<&str> = order.split(' ').collect();
+    let error = |order: &str| match Deck::create_from(order) {
+        Ok(_) => panic!("{} was accepted", order),
+        Err(error) => error,
+    };
+
+    let missing = codes[1..].join(" ");
+    assert_eq!(error(&missing), "a deck has 52 cards, not 51");
+    let extra = format!("{} 2c", order);
+    assert_eq!(error(&extra), "a deck has 52 cards, not 53");
+
+    let mut duplicated = codes.clone();
+    duplicated[1] = "3h";
+    let duplicate = error(&duplicated.join(" "));
+    assert!(duplicate.contains("in the deck twice"), "{}", duplicate);
+
+    let mut unknown = codes.clone();
+    unknown[5] = "1x";
+    assert_eq!(error(&unknown.join(" ")), "unknown card 1x");
+    assert_eq!(error(&order.replace(' ', "")[1..]), "unknown card h3");
+}
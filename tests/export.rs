@@ -0,0 +1,119 @@
+use gin::export::{hand_rows, to_csv, to_json, ExportFilter, HandRow};
+use gin::record::{parse_date, HandRecord};
+use serde_json::Value;
+
+// the recorded hand, played 2023.11.14
+fn recorded() -> HandRecord {
+    HandRecord::load("tests/hands/42-1.log").unwrap()
+}
+
+fn row(first_player: &str, second_player: &str) -> HandRow {
+    HandRow {
+        match_id: 7,
+        hand: 2,
+        date: String::from("2026.10.19"),
+        rules: String::from("Standard"),
+        seed: u64::MAX,
+        first_player: String::from(first_player),
+        second_player: String::from(second_player),
+        knocker: String::from(first_player),
+        outcome: String::from("knock"),
+        winner: String::from(first_player),
+        points: 12,
+        knocker_deadwood: 4,
+        defender_deadwood: 16,
+        turns: 9,
+    }
+}
+
+#[test]
+fn a_recorded_hand_becomes_one_row() {
+    let rows = hand_rows(&[recorded()], &ExportFilter::new());
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!((row.match_id, row.hand), (42, 1));
+    assert_eq!(row.date, "2023.11.14");
+    assert_eq!(row.rules, "Quick");
+    assert_eq!(row.knocker, "Grace");
+    assert_eq!(row.winner, "Grace");
+    assert_eq!(row.turns, 6);
+}
+
+#[test]
+fn the_filter_picks_hands_by_player_rules_and_date() {
+    let record = recorded();
+    let day = parse_date("2023.11.14").unwrap();
+    let includes = |filter: ExportFilter| filter.includes(&record);
+    assert!(includes(ExportFilter::new()));
+    assert!(includes(ExportFilter {
+        player: Some(String::from("Ada")),
+        rules: Some(String::from("Quick")),
+        ..ExportFilter::new()
+    }));
+    assert!(!includes(ExportFilter {
+        player: Some(String::from("Bob")),
+        ..ExportFilter::new()
+    }));
+    assert!(!includes(ExportFilter {
+        rules: Some(String::from("Standard")),
+        ..ExportFilter::new()
+    }));
+    // from is inclusive and to exclusive
+    assert!(includes(ExportFilter {
+        from: Some(day),
+        to: Some(day + 86400),
+        ..ExportFilter::new()
+    }));
+    assert!(!includes(ExportFilter {
+        from: Some(day + 86400),
+        ..ExportFilter::new()
+    }));
+    assert!(!includes(ExportFilter {
+        to: Some(record.played),
+        ..ExportFilter::new()
+    }));
+
+    // a hand left out still counts towards the numbering of its match
+    let mut later = recorded();
+    later.played += 86400;
+    let filter = ExportFilter {
+        from: Some(day + 86400),
+        ..ExportFilter::new()
+    };
+    let rows = hand_rows(&[recorded(), later], &filter);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].hand, 2);
+}
+
+#[test]
+fn csv_values_are_quoted_when_they_need_it() {
+    let csv = to_csv(&[row("Smith, Jo", "Jo \"the card\"\nSmith")]);
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "match,hand,date,rules,seed,first_player,second_player,knocker,outcome,winner,points,\
+         knocker_deadwood,defender_deadwood,turns"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "7,2,2026.10.19,Standard,18446744073709551615,\"Smith, Jo\",\"Jo \"\"the card\"\""
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "Smith\",\"Smith, Jo\",knock,\"Smith, Jo\",12,4,16,9"
+    );
+    assert_eq!(to_csv(&[]).lines().count(), 1);
+}
+
+#[test]
+fn json_output_escapes_text_and_keeps_numbers() {
+    let json = to_json(&[row("Ada", "Back\\slash \"quoted\"\n"), row("Grace", "Ada")]);
+    let rows: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+    assert_eq!(rows[0]["second_player"], "Back\\slash \"quoted\"\n");
+    assert_eq!(rows[0]["points"], 12);
+    // seeds are too big for a JSON number to hold exactly
+    assert_eq!(rows[0]["seed"], "18446744073709551615");
+    assert_eq!(rows[1]["winner"], "Grace");
+    assert_eq!(to_json(&[]), "[]");
+}
@@ -0,0 +1,75 @@
+use gin::command::{parse, parse_card, Command};
+use gin::hand::HandOrder;
+use gin::record::HandRecord;
+use gin::{Card, GinGame};
+
+fn cards(codes: &str) -> Vec<Card> {
+    codes
+        .split_whitespace()
+        .map(|code| parse_card(code).unwrap())
+        .collect()
+}
+
+// Grace to draw, holding threes, sixes, two aces, Kd and 5h
+fn grace_to_draw() -> GinGame {
+    HandRecord::load("tests/hands/42-1.log")
+        .unwrap()
+        .play_to(2)
+        .unwrap()
+}
+
+#[test]
+fn hands_are_arranged_by_suit_rank_or_pinned_cards() {
+    let game = grace_to_draw();
+    let hand = game.view("Grace").hand;
+    assert_eq!(HandOrder::Arrival.arrange(&hand), hand);
+    assert_eq!(
+        HandOrder::Suit.arrange(&hand),
+        cards("Ac 3c As 3s 6s 3d 6d Kd 5h 6h")
+    );
+    assert_eq!(
+        HandOrder::Rank.arrange(&hand),
+        cards("Ac As 3c 3s 3d 5h 6s 6d 6h Kd")
+    );
+    // the pinned cards first, then the others as they arrived
+    let pinned = cards("Kd Ac");
+    let mut expected = pinned.clone();
+    expected.extend(hand.iter().filter(|card| !pinned.contains(card)).cloned());
+    assert_eq!(HandOrder::Pinned(pinned).arrange(&hand), expected);
+}
+
+#[test]
+fn sorting_changes_only_the_view_of_the_hand() {
+    let mut game = grace_to_draw();
+    let hand = game.view("Grace").hand;
+    game.sort_hand(HandOrder::Rank).unwrap();
+    let view = game.view("Grace");
+    assert_eq!(view.order, HandOrder::Rank);
+    assert_eq!(view.hand, hand);
+    assert_eq!(game.view("Ada").order, HandOrder::Arrival);
+
+    let error = game.sort_hand(HandOrder::Pinned(cards("Qh"))).unwrap_err();
+    assert!(error.contains("do not hold"), "{}", error);
+    let error = game
+        .sort_hand(HandOrder::Pinned(cards("Kd Kd")))
+        .unwrap_err();
+    assert!(error.contains("twice"), "{}", error);
+    assert_eq!(game.view("Grace").order, HandOrder::Rank);
+}
+
+#[test]
+fn sort_commands_are_parsed() {
+    assert_eq!(parse("sort suits").unwrap(), Command::Sort(HandOrder::Suit));
+    assert_eq!(parse("sort rank").unwrap(), Command::Sort(HandOrder::Rank));
+    assert_eq!(parse("unsort").unwrap(), Command::Sort(HandOrder::Arrival));
+    assert_eq!(
+        parse("pin 7h, 8h and 9h").unwrap(),
+        Command::Sort(HandOrder::Pinned(cards("7h 8h 9h")))
+    );
+    assert!(parse("sort").unwrap_err().contains("sort suit"));
+    assert_eq!(
+        HandOrder::Pinned(cards("7h")).next_hand(),
+        HandOrder::Arrival
+    );
+    assert_eq!(HandOrder::Suit.next_hand(), HandOrder::Suit);
+}
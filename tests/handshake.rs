@@ -0,0 +1,67 @@
+mod common;
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions, PROTOCOL_VERSION};
+use gin::server::Lobby;
+
+#[test]
+fn clients_that_skip_hello_or_speak_another_version_are_turned_away() {
+    let address = start_server(Lobby::shared());
+
+    let mut rude = Client::unintroduced(&address, "Alice");
+    rude.send(&ClientMessage::Name(String::from("Alice")));
+    assert!(rude.error().contains("say hello first"));
+    assert!(rude.closed());
+
+    let mut future = Client::unintroduced(&address, "Bob");
+    future.send(&ClientMessage::Hello(PROTOCOL_VERSION + 1, Vec::new()));
+    assert!(future.error().contains("is not supported"));
+    assert!(future.closed());
+}
+
+#[test]
+fn only_features_both_ends_support_are_agreed_and_used() {
+    let address = start_server(Lobby::shared());
+
+    let mut client = Client::unintroduced(&address, "Alice");
+    let wanted = vec![
+        String::from("rules:Standard"),
+        String::from("spectate"),
+        String::from("teleport"),
+    ];
+    client.send(&ClientMessage::Hello(PROTOCOL_VERSION, wanted));
+    match client.receive() {
+        ServerMessage::Welcome(version, features) => {
+            assert_eq!(version, PROTOCOL_VERSION);
+            assert_eq!(features, vec!["spectate", "rules:Standard"]);
+        }
+        _ => panic!("expected a welcome"),
+    }
+
+    client.send(&ClientMessage::Name(String::from("Alice")));
+    client.send(&ClientMessage::Games);
+    assert!(client.error().contains("correspondence"));
+    client.send(&ClientMessage::Match(String::from("Quick")));
+    assert!(client.error().contains("rules:Quick"));
+
+    // a table with rules the client did not agree to cannot be joined
+    let mut host = Client::connect(&address, "Bob");
+    host.send(&ClientMessage::Create(TableOptions {
+        rules: String::from("Quick"),
+        ..TableOptions::standard()
+    }));
+    let table = host.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    client.send(&ClientMessage::Join(Some(table)));
+    assert!(client.error().contains("no such table"));
+}
+
+#[test]
+fn hello_is_only_said_once() {
+    let address = start_server(Lobby::shared());
+    let mut client = Client::connect(&address, "Alice");
+    client.send(&ClientMessage::Hello(PROTOCOL_VERSION, Vec::new()));
+    assert_eq!(client.error(), "hello was already said");
+}
@@ -0,0 +1,48 @@
+// runs the gin command, which is only built with the terminal feature
+#![cfg(feature = "terminal")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// the deck of the recorded match, so both hands are known
+const DECK: &str = "6c 2h Jc 8d 8s Kh 7c 4c 5s Th 2s 4h Kc Qh 5c 2c Ah Jh 7s Qs 4s 7d Ts 2d 8c \
+                    Td Ad 9d Qd 9c 8h Qc As Ks Ac 5d 6s Jd 3d 9h Kd 7h 5h Js 6h 4d 6d 9s 3c Tc 3s 3h";
+
+// plays the first turn of a hot seat match, with the given input
+fn play(input: &str) -> String {
+    let mut gin = Command::new(env!("CARGO_BIN_EXE_gin"))
+        .args(["--ascii", "--hot-seat", "--deck", DECK])
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    gin.stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = gin.wait_with_output().unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn hands_are_only_shown_once_the_player_has_the_device() {
+    let output = play("\ndraw\n");
+    let (before, after) = output.split_once("Pass the device to ").unwrap();
+    assert!(!before.contains("hand:"));
+    // the screen and its scrollback are cleared first
+    assert!(before.ends_with("\x1b[2J\x1b[3J\x1b[H"));
+
+    let player = after.split(',').next().unwrap();
+    let opponent = if player == "Mitch" { "Phoebe" } else { "Mitch" };
+    assert!(after.contains(&format!("{}'s hand:", player)));
+    assert!(!after.contains(&format!("{}'s hand:", opponent)));
+}
+
+#[test]
+fn nothing_is_shown_until_enter_is_pressed() {
+    let output = play("");
+    let (_, after) = output.split_once("Pass the device to ").unwrap();
+    assert!(!after.contains("hand:"));
+}
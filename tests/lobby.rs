@@ -0,0 +1,144 @@
+mod common;
+
+use std::thread;
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions};
+use gin::record::new_match_id;
+use gin::server::Lobby;
+
+fn create(address: &str, name: &str, options: TableOptions) -> (Client, u32) {
+    let mut host = Client::connect(address, name);
+    host.send(&ClientMessage::Create(options));
+    let table = host.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    (host, table)
+}
+
+#[test]
+fn clients_find_tables_by_list_and_invite() {
+    let address = start_server(Lobby::shared());
+    let mut hosts = Vec::new();
+    for index in 0..12u32 {
+        let options = TableOptions {
+            rules: String::from(if index % 2 == 0 { "Standard" } else { "Quick" }),
+            stakes: index + 1,
+            private: false,
+        };
+        hosts.push(create(&address, &format!("host-{}", index), options));
+    }
+    let (mut private_host, _) = create(
+        &address,
+        "private-host",
+        TableOptions {
+            private: true,
+            ..TableOptions::standard()
+        },
+    );
+    let code = private_host.wait_for(|message| match message {
+        ServerMessage::Invite(code) => Some(code),
+        _ => None,
+    });
+
+    let mut browser = Client::connect(&address, "browser");
+    browser.send(&ClientMessage::List);
+    let tables = browser.wait_for(|message| match message {
+        ServerMessage::Tables(tables) => Some(tables),
+        _ => None,
+    });
+    assert_eq!(tables.len(), 12);
+    for (host, table) in hosts.iter() {
+        let listed = tables.iter().find(|open| open.table == *table).unwrap();
+        assert_eq!(listed.host, host.name);
+    }
+    assert!(tables.iter().all(|open| open.host != "private-host"));
+    assert!(tables
+        .iter()
+        .any(|open| open.rules == "Quick" && open.stakes == 12));
+
+    let mut guest = Client::connect(&address, "guest");
+    guest.send(&ClientMessage::Invite(String::from("NOSUCH")));
+    guest.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+    guest.send(&ClientMessage::Invite(code));
+    assert_eq!(guest.opponent(), "private-host");
+    assert_eq!(private_host.opponent(), "guest");
+
+    let joiners: Vec<_> = hosts
+        .into_iter()
+        .enumerate()
+        .map(|(index, (mut host, table))| {
+            let address = address.clone();
+            thread::spawn(move || {
+                let name = format!("joiner-{}", index);
+                let mut joiner = Client::connect(&address, &name);
+                joiner.send(&ClientMessage::Join(Some(table)));
+                let rules = if index % 2 == 0 { "Standard" } else { "Quick" };
+                let sat_down = joiner.wait_for(|message| match message {
+                    ServerMessage::Info(text) if text.contains("sit down") => Some(text),
+                    _ => None,
+                });
+                assert!(sat_down.contains(rules));
+                assert_eq!(joiner.opponent(), host.name);
+                assert_eq!(host.opponent(), name);
+            })
+        })
+        .collect();
+    for joiner in joiners {
+        joiner.join().unwrap();
+    }
+
+    browser.send(&ClientMessage::List);
+    let tables = browser.wait_for(|message| match message {
+        ServerMessage::Tables(tables) => Some(tables),
+        _ => None,
+    });
+    assert!(tables.is_empty());
+}
+
+#[test]
+fn waiting_players_are_matched_under_the_same_rules() {
+    let address = start_server(Lobby::shared());
+    let seekers: Vec<_> = (0..24)
+        .map(|index| {
+            let address = address.clone();
+            thread::spawn(move || {
+                let rules = if index % 3 == 0 { "Quick" } else { "Standard" };
+                let name = format!("{}-seeker-{}", rules, index);
+                let mut seeker = Client::connect(&address, &name);
+                seeker.send(&ClientMessage::Match(String::from(rules)));
+                let opponent = seeker.opponent();
+                assert!(opponent.starts_with(rules));
+                assert_ne!(opponent, name);
+            })
+        })
+        .collect();
+    for seeker in seekers {
+        seeker.join().unwrap();
+    }
+
+    let mut seeker = Client::connect(&address, "seeker");
+    seeker.send(&ClientMessage::Match(String::from("Oklahoma")));
+    seeker.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+}
+
+#[test]
+fn tables_starting_together_get_their_own_match_ids() {
+    let starts: Vec<_> = (0..8)
+        .map(|_| thread::spawn(|| (0..50).map(|_| new_match_id()).collect::<Vec<_>>()))
+        .collect();
+    let mut ids: Vec<u64> = starts
+        .into_iter()
+        .flat_map(|start| start.join().unwrap())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 400);
+}
@@ -0,0 +1,95 @@
+use gin::notation::{parse, write};
+use gin::record::HandRecord;
+
+fn recorded() -> HandRecord {
+    HandRecord::load("tests/hands/42-1.log").unwrap()
+}
+
+// the log without the time it was played, which notation only keeps to the day
+fn log_without_time(record: &HandRecord) -> String {
+    record
+        .to_log()
+        .lines()
+        .filter(|line| !line.starts_with("played "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn a_hand_is_written_in_notation() {
+    let text = write(&recorded());
+    for line in [
+        "[Match \"42\"]",
+        "[Player1 \"Ada\"]",
+        "[Player2 \"Grace\"]",
+        "[Date \"2023.11.14\"]",
+        "[Rules \"Quick\"]",
+        "[FirstTurn \"Ada\"]",
+        "[Hand1 \"3h Tc 9s 4d Js 7h 9h Jd 5d Ks\"]",
+        "[Hand2 \"3s 3c 6d 6h 5h Kd 3d 6s Ac As\"]",
+        "[Upcard \"Qc\"]",
+        "[Result \"Grace 42\"]",
+        "1. P:take-upcard D:Ks | S:draw-stock D:Kd",
+        "3. S:draw-stock D:Js | S:draw-stock D:8h K",
+        "Grace: M:3s,3c,3d M:6d,6h,6s M:Ac,As,Ad",
+        "Ada: M:9s,9h,9c,9d L:3h@0",
+    ] {
+        assert!(text.lines().any(|written| written == line), "{}", line);
+    }
+}
+
+#[test]
+fn notation_reads_back_to_the_same_hand() {
+    let record = recorded();
+    let text = write(&record);
+    let parsed = parse(&text).unwrap();
+    assert_eq!(log_without_time(&parsed), log_without_time(&record));
+    assert_eq!(write(&parsed), text);
+    // a hand in notation loads like a saved log
+    let path = std::env::temp_dir().join("gin-notation-42-1.gin");
+    std::fs::write(&path, &text).unwrap();
+    let loaded = HandRecord::load(path.to_str().unwrap()).unwrap();
+    assert_eq!(log_without_time(&loaded), log_without_time(&record));
+}
+
+#[test]
+fn notation_errors_say_what_is_wrong() {
+    let text = write(&recorded());
+    let error = |from: &str, to: &str| match parse(&text.replacen(from, to, 1)) {
+        Ok(_) => panic!("{} was read", to),
+        Err(error) => error,
+    };
+    assert_eq!(error("[Match \"42\"]", "[Match \"x\"]"), "invalid match: x");
+    assert_eq!(
+        error("[Date \"2023.11.14\"]", "[Date \"2023.13.40\"]"),
+        "invalid date: 2023.13.40"
+    );
+    assert_eq!(
+        error("[Result \"Grace 42\"]", "[Result \"Grace\"]"),
+        "invalid result: Grace"
+    );
+    assert_eq!(
+        error("[Upcard \"Qc\"]", "[Upcard \"Qx\"]"),
+        "unknown card Qx"
+    );
+    assert_eq!(
+        error("[Upcard \"Qc\"]", "[Upcard \"\"]"),
+        "the deal needs two hands of ten cards and an upcard"
+    );
+    assert_eq!(
+        error("[Rules", "[Rules\"\"]\n[Rules"),
+        "invalid tag: [Rules\"\"]"
+    );
+    assert_eq!(
+        error("P:take-upcard", "X:take-upcard"),
+        "unknown move X:take-upcard"
+    );
+    assert_eq!(
+        error("[FirstTurn \"Ada\"]", ""),
+        "moves must come after the FirstTurn tag"
+    );
+    assert_eq!(
+        error("Grace: ", "Grace "),
+        "could not read line: Grace M:3s,3c,3d M:6d,6h,6s M:Ac,As,Ad"
+    );
+}
@@ -0,0 +1,80 @@
+use gin::rating::{Ladder, Rating};
+use gin::record::HandRecord;
+use gin::stats::{match_results, PlayerProfile};
+
+fn rated(rating: f64, deviation: f64) -> Rating {
+    Rating {
+        rating,
+        deviation,
+        volatility: 0.06,
+    }
+}
+
+#[test]
+fn the_worked_example_from_the_glicko_2_paper() {
+    // a 1500 player with deviation 200 beats a 1400 and loses to a 1550 and a 1700
+    let player = rated(1500.0, 200.0);
+    let games = [
+        (rated(1400.0, 30.0), 1.0),
+        (rated(1550.0, 100.0), 0.0),
+        (rated(1700.0, 300.0), 0.0),
+    ];
+    let after = player.update_period(&games);
+    assert!((after.rating - 1464.06).abs() < 0.01, "{}", after.rating);
+    assert!(
+        (after.deviation - 151.52).abs() < 0.01,
+        "{}",
+        after.deviation
+    );
+    assert!(
+        (after.volatility - 0.05999).abs() < 0.00001,
+        "{}",
+        after.volatility
+    );
+}
+
+#[test]
+fn a_match_moves_the_winner_up_and_the_loser_down() {
+    let winner = Rating::new();
+    let loser = rated(1600.0, 80.0);
+    let won = winner.update(&loser, 1.0);
+    let lost = loser.update(&winner, 0.0);
+    assert!(won.rating > winner.rating);
+    assert!(lost.rating < loser.rating);
+    // the newcomer is less certain, so moves further
+    assert!(won.rating - winner.rating > loser.rating - lost.rating);
+    assert!(won.deviation < winner.deviation);
+}
+
+#[test]
+fn names_with_accounts_are_only_rated_when_logged_in() {
+    let hand = |match_id: u64, accounts: &[&str]| {
+        let mut record = HandRecord::load("tests/hands/42-1.log").unwrap();
+        record.match_id = match_id;
+        record.played += match_id;
+        // enough to take the Quick match
+        record.points = 60;
+        record.accounts = accounts
+            .iter()
+            .map(|account| String::from(*account))
+            .collect();
+        record
+    };
+    // someone else sits down as Grace without logging in
+    let records = [hand(1, &["Ada", "Grace"]), hand(2, &["Ada"]), hand(3, &[])];
+    let results = match_results(&records);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].match_id, 1);
+
+    let ladder = Ladder::from_results(&results);
+    assert_eq!(ladder.player("Grace", "Quick").unwrap().matches, 1);
+    assert_eq!(
+        PlayerProfile::from_records("Grace", &records).hands_played,
+        1
+    );
+    assert_eq!(PlayerProfile::from_records("Ada", &records).hands_played, 2);
+
+    // without accounts anywhere, names are taken as they are
+    let records = [hand(4, &[]), hand(5, &[])];
+    assert_eq!(match_results(&records).len(), 2);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions};
+use gin::record::HandRecord;
+use gin::server::{Lobby, Reconnection, ServerOptions};
+use gin::stats::{match_results, PlayerProfile};
+use gin::{Action, Phase};
+
+fn seat_two(reconnection: Reconnection) -> (String, Client, Client) {
+    let address = start_server(Lobby::create(ServerOptions {
+        reconnection,
+        ..ServerOptions::standard()
+    }));
+    let mut alice = Client::connect(&address, "Alice");
+    alice.send(&ClientMessage::Create(TableOptions::standard()));
+    // Bob would open a table of their own if he looked before Alice's was open
+    alice.wait_for(|message| match message {
+        ServerMessage::Table(_) => Some(()),
+        _ => None,
+    });
+    let mut bob = Client::connect(&address, "Bob");
+    bob.send(&ClientMessage::Join(None));
+    (address, alice, bob)
+}
+
+#[test]
+fn a_dropped_player_takes_back_their_seat() {
+    let (address, mut alice, mut bob) = seat_two(Reconnection::standard());
+    let session = alice.session();
+    assert_eq!(alice.opponent(), "Bob");
+    drop(alice);
+
+    bob.wait_for_info("Alice has lost their connection");
+    bob.send(&ClientMessage::Play(Action::DrawStock));
+    let refused = bob.error();
+    assert!(refused.contains("paused"));
+
+    let mut alice = Client::resume(&address, "Alice", &session);
+    alice.wait_for_info("you are back");
+    let view = alice.wait_for(|message| match message {
+        ServerMessage::View(view) => Some(view),
+        _ => None,
+    });
+    assert_eq!(view.player, "Alice");
+    assert_eq!(view.hand.len(), 10);
+    bob.wait_for_info("Alice is back");
+}
+
+#[test]
+fn a_seat_not_taken_back_goes_to_a_bot() {
+    let (_, mut alice, mut bob) = seat_two(Reconnection {
+        timeout: Duration::from_millis(500),
+        bot: true,
+    });
+    alice.opponent();
+    drop(alice);
+
+    bob.wait_for_info("a bot plays for them");
+    // Bob plays until the bot has made a move of its own
+    loop {
+        match bob.receive() {
+            ServerMessage::Moved(player, _) => {
+                assert_eq!(player, "Alice");
+                break;
+            }
+            ServerMessage::View(view) if view.turn == "Bob" => match view.phase {
+                Phase::Draw => bob.send(&ClientMessage::Play(Action::DrawStock)),
+                Phase::Discard => {
+                    let card = view.hand.last().unwrap().clone();
+                    bob.send(&ClientMessage::Play(Action::Discard(card)));
+                }
+                _ => {}
+            },
+            ServerMessage::Result(_, _) => break,
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn a_seat_not_taken_back_is_forfeited() {
+    let (address, mut alice, mut bob) = seat_two(Reconnection {
+        timeout: Duration::from_millis(500),
+        bot: false,
+    });
+    let session = alice.session();
+    alice.opponent();
+    drop(alice);
+
+    bob.wait_for_info("Alice has not come back and forfeits the match");
+    let mut alice = Client::resume(&address, "Alice", &session);
+    alice.wait_for(|message| match message {
+        ServerMessage::Error(text) if text.contains("session is over") => Some(()),
+        _ => None,
+    });
+}
+
+#[test]
+fn a_forfeit_is_counted_as_a_match_won() {
+    // names no other test plays under, since every server saves its hands in the same place
+    let before = PlayerProfile::from_records("Jonas", &HandRecord::load_dir("hands"));
+    let address = start_server(Lobby::create(ServerOptions {
+        reconnection: Reconnection {
+            timeout: Duration::from_millis(500),
+            bot: false,
+        },
+        ..ServerOptions::standard()
+    }));
+    let mut ines = Client::connect(&address, "Ines");
+    ines.send(&ClientMessage::Create(TableOptions::standard()));
+    ines.wait_for(|message| match message {
+        ServerMessage::Table(_) => Some(()),
+        _ => None,
+    });
+    let mut jonas = Client::connect(&address, "Jonas");
+    jonas.send(&ClientMessage::Join(None));
+    ines.opponent();
+    drop(ines);
+
+    jonas.wait_for_info("Jonas wins the match by forfeit");
+    let records = HandRecord::load_dir("hands");
+    let after = PlayerProfile::from_records("Jonas", &records);
+    assert_eq!(after.matches_played, before.matches_played + 1);
+    assert_eq!(after.matches_won, before.matches_won + 1);
+    // the hand cut short is not counted as one played to the end
+    assert_eq!(after.hands_played, before.hands_played);
+    let result = match_results(&records)
+        .into_iter()
+        .filter(|result| result.first_player == "Ines" || result.second_player == "Ines")
+        .max_by_key(|result| result.played)
+        .unwrap();
+    assert_eq!(result.winner.as_deref(), Some("Jonas"));
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use gin::cards::CardStyle;
+use gin::shuffle::{card, card_values, check_shuffle, encrypt_and_shuffle, open, ShuffleKey};
+use gin::Card;
+use num_bigint::BigUint;
+
+fn code(card: &Card) -> String {
+    card.face(CardStyle {
+        symbols: false,
+        colour: false,
+    })
+}
+
+#[test]
+fn a_deck_shuffled_by_both_peers_opens_to_a_full_pack() {
+    let keys = [ShuffleKey::generate(), ShuffleKey::generate()];
+    let first = encrypt_and_shuffle(&card_values(), &keys[0]);
+    let second = encrypt_and_shuffle(&first, &keys[1]);
+    assert!(check_shuffle(&card_values(), &first, &keys[0]));
+    assert!(check_shuffle(&first, &second, &keys[1]));
+
+    let cards = open(&second, [&keys[0], &keys[1]]).unwrap();
+    let codes: HashSet<String> = cards.iter().map(code).collect();
+    assert_eq!(codes.len(), 52);
+    // the locks come off in either order
+    let other_order = open(&second, [&keys[1], &keys[0]]).unwrap();
+    assert_eq!(cards, other_order);
+}
+
+#[test]
+fn a_shuffle_with_a_card_swapped_is_refused() {
+    let key = ShuffleKey::generate();
+    let shuffled = encrypt_and_shuffle(&card_values(), &key);
+    let mut swapped = shuffled.clone();
+    swapped[0] = key.encrypt(&BigUint::from(7u32));
+    assert!(!check_shuffle(&card_values(), &swapped, &key));
+    // nor may one card stand in for another
+    let mut doubled = shuffled.clone();
+    doubled[0] = doubled[1].clone();
+    assert!(!check_shuffle(&card_values(), &doubled, &key));
+    // and a shuffle under another key does not pass for this one
+    let other = ShuffleKey::generate();
+    assert!(!check_shuffle(&card_values(), &shuffled, &other));
+}
+
+#[test]
+fn a_revealed_key_must_match_its_commitment() {
+    let key = ShuffleKey::generate();
+    let revealed = ShuffleKey::reveal(
+        key.encrypt_key().clone(),
+        key.nonce().clone(),
+        &key.commitment(),
+    )
+    .unwrap();
+    assert_eq!(revealed.commitment(), key.commitment());
+
+    let other = ShuffleKey::generate();
+    let error = ShuffleKey::reveal(
+        other.encrypt_key().clone(),
+        other.nonce().clone(),
+        &key.commitment(),
+    )
+    .err()
+    .unwrap();
+    assert!(error.contains("not the one committed to"), "{}", error);
+    let error = ShuffleKey::reveal(
+        key.encrypt_key().clone(),
+        key.nonce() + 1u32,
+        &key.commitment(),
+    )
+    .err()
+    .unwrap();
+    assert!(error.contains("not the one committed to"), "{}", error);
+}
+
+#[test]
+fn a_card_still_locked_cannot_be_read() {
+    let keys = [ShuffleKey::generate(), ShuffleKey::generate()];
+    let value = &card_values()[0];
+    let locked = keys[1].encrypt(&keys[0].encrypt(value));
+    assert!(card(&keys[0].decrypt(&locked)).is_err());
+    let unlocked = card(&keys[1].decrypt(&keys[0].decrypt(&locked))).unwrap();
+    assert_eq!(code(&unlocked), "Ac");
+}
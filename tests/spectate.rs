@@ -0,0 +1,140 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{start_server, Client};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions};
+use gin::server::{Lobby, ServerOptions};
+use gin::Action;
+
+// starts a match between Alice and Bob, and returns its table and who goes first
+fn start_match(address: &str, options: TableOptions) -> (u32, Client, Client) {
+    let mut alice = Client::connect(address, "Alice");
+    alice.send(&ClientMessage::Create(options));
+    let table = alice.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    let mut bob = Client::connect(address, "Bob");
+    bob.send(&ClientMessage::Join(Some(table)));
+    let first = alice.wait_for(|message| match message {
+        ServerMessage::Info(text) if text.starts_with("new hand") => Some(text),
+        _ => None,
+    });
+    if first.contains("Alice goes first") {
+        (table, alice, bob)
+    } else {
+        (table, bob, alice)
+    }
+}
+
+fn watch(address: &str, table: u32) -> Client {
+    let mut carol = Client::connect(address, "Carol");
+    carol.send(&ClientMessage::Watch(table));
+    carol.wait_for(|message| match message {
+        ServerMessage::Info(text) if text.contains("you are watching Alice and Bob") => Some(()),
+        _ => None,
+    });
+    carol
+}
+
+#[test]
+fn spectators_see_the_moves_as_they_happen() {
+    let address = start_server(Lobby::shared());
+    let (table, mut first, mut second) = start_match(&address, TableOptions::standard());
+
+    let mut carol = Client::connect(&address, "Carol");
+    carol.send(&ClientMessage::List);
+    carol.wait_for(|message| match message {
+        ServerMessage::Info(text)
+            if text == format!("table {} - Alice and Bob are playing", table) =>
+        {
+            Some(())
+        }
+        _ => None,
+    });
+    carol.send(&ClientMessage::Watch(table + 1));
+    carol.wait_for(|message| match message {
+        ServerMessage::Error(_) => Some(()),
+        _ => None,
+    });
+    let mut carol = watch(&address, table);
+
+    first.play_turn();
+    second.play_turn();
+    let mut moves = Vec::new();
+    while moves.len() < 4 {
+        match carol.receive() {
+            ServerMessage::Moved(player, action) => moves.push((player, action)),
+            ServerMessage::Watched(_) => panic!("the hands are secret at this server"),
+            _ => {}
+        }
+    }
+    let players: Vec<&str> = moves.iter().map(|(player, _)| player.as_str()).collect();
+    assert_eq!(
+        players,
+        [&first.name, &first.name, &second.name, &second.name]
+    );
+    assert!(moves[0].1 == Action::DrawStock && moves[2].1 == Action::DrawStock);
+    assert!(matches!(moves[1].1, Action::Discard(_)));
+}
+
+#[test]
+fn spectators_see_the_hands_after_the_delay() {
+    let reveal = Duration::from_millis(500);
+    let address = start_server(Lobby::create(ServerOptions {
+        reveal: Some(reveal),
+        ..ServerOptions::standard()
+    }));
+    let (table, mut first, _second) = start_match(&address, TableOptions::standard());
+    let watching = Instant::now();
+    let mut carol = watch(&address, table);
+
+    first.play_turn();
+    let mut moved = false;
+    let mut hands = Vec::new();
+    while hands.len() < 2 {
+        match carol.receive() {
+            ServerMessage::Moved(_, _) => moved = true,
+            ServerMessage::Watched(view) => {
+                // the moves are seen before the hands they were made from
+                assert!(moved);
+                assert!(watching.elapsed() >= reveal);
+                assert_eq!(view.hand.len(), 10);
+                hands.push(view.player);
+            }
+            _ => {}
+        }
+    }
+    hands.sort();
+    assert_eq!(hands, ["Alice", "Bob"]);
+}
+
+#[test]
+fn invite_only_matches_cannot_be_watched() {
+    let address = start_server(Lobby::shared());
+    let options = TableOptions {
+        private: true,
+        ..TableOptions::standard()
+    };
+    let mut alice = Client::connect(&address, "Alice");
+    alice.send(&ClientMessage::Create(options));
+    let (table, code) = (
+        alice.wait_for(|message| match message {
+            ServerMessage::Table(table) => Some(table),
+            _ => None,
+        }),
+        alice.wait_for(|message| match message {
+            ServerMessage::Invite(code) => Some(code),
+            _ => None,
+        }),
+    );
+    let mut bob = Client::connect(&address, "Bob");
+    bob.send(&ClientMessage::Invite(code));
+    assert_eq!(bob.opponent(), "Alice");
+
+    let mut carol = Client::connect(&address, "Carol");
+    carol.send(&ClientMessage::Watch(table));
+    let refused = carol.error();
+    assert!(refused.contains("no match to watch"));
+}
@@ -0,0 +1,102 @@
+use gin::record::HandRecord;
+use gin::stats::{HandSummary, PlayerProfile};
+
+// Grace knocks in both saved hands of match 42: with 5 deadwood against Ada's 36 in the first,
+// and with 10 against Ada's 21 in the second, taking the Quick match with 63 points
+fn saved_hands() -> Vec<HandRecord> {
+    HandRecord::load_dir("tests/hands")
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-9
+}
+
+#[test]
+fn saved_hands_are_summarised() {
+    let records = saved_hands();
+    let summaries: Vec<HandSummary> = records
+        .iter()
+        .map(|record| HandSummary::from_record(record).unwrap())
+        .collect();
+    let ended: Vec<(i32, i32, i32)> = summaries
+        .iter()
+        .map(|summary| {
+            (
+                summary.points,
+                summary.knocker_deadwood,
+                summary.defender_deadwood,
+            )
+        })
+        .collect();
+    assert_eq!(ended, [(42, 5, 36), (21, 10, 21)]);
+    for summary in summaries.iter() {
+        assert_eq!(summary.knocker, "Grace");
+        assert_eq!(summary.winner, "Grace");
+        assert!(!summary.gin);
+        assert!(!summary.undercut);
+    }
+}
+
+#[test]
+fn the_knocker_is_profiled_from_the_saved_hands() {
+    let grace = PlayerProfile::from_records("Grace", &saved_hands());
+    assert_eq!(grace.hands_played, 2);
+    assert_eq!(grace.hands_won, 2);
+    assert_eq!((grace.matches_won, grace.matches_played), (1, 1));
+    assert!(close(grace.gin_rate(), 0.0));
+    assert!(close(grace.knock_rate(), 1.0));
+    assert!(close(grace.undercut_received_rate(), 0.0));
+    assert!(close(grace.average_knock_deadwood(), 7.5));
+    assert!(close(grace.points_per_hand(), 31.5));
+}
+
+#[test]
+fn the_defender_is_profiled_from_the_saved_hands() {
+    let ada = PlayerProfile::from_records("Ada", &saved_hands());
+    assert_eq!(ada.hands_played, 2);
+    assert_eq!(ada.hands_won, 0);
+    assert_eq!((ada.matches_won, ada.matches_played), (0, 1));
+    assert_eq!(ada.opponent_knocks, 2);
+    assert!(close(ada.gin_rate(), 0.0));
+    assert!(close(ada.knock_rate(), 0.0));
+    assert!(close(ada.undercut_given_rate(), 0.0));
+    // Ada never knocked, so there is nothing to average
+    assert!(close(ada.average_knock_deadwood(), 0.0));
+}
+
+#[test]
+fn gins_and_undercuts_change_the_rates() {
+    let summary = |knocker: &str, winner: &str, gin: bool, deadwood: i32| HandSummary {
+        knocker: String::from(knocker),
+        gin,
+        undercut: !gin && winner != knocker,
+        winner: String::from(winner),
+        points: 25,
+        knocker_deadwood: deadwood,
+        defender_deadwood: 4,
+        turns: 8,
+    };
+    let records = saved_hands();
+    let mut grace = PlayerProfile::from_records("Grace", &records);
+    let mut ada = PlayerProfile::from_records("Ada", &records);
+    // Ada undercuts a third knock from Grace, then goes gin
+    for hand in [
+        summary("Grace", "Ada", false, 6),
+        summary("Ada", "Ada", true, 0),
+    ] {
+        grace.add_hand(&hand);
+        ada.add_hand(&hand);
+    }
+
+    assert!(close(grace.gin_rate(), 0.0));
+    assert!(close(grace.knock_rate(), 0.75));
+    assert!(close(grace.undercut_received_rate(), 1.0 / 3.0));
+    assert!(close(grace.average_knock_deadwood(), 7.0));
+    // a gin is not a knock the opponent could undercut
+    assert_eq!(grace.opponent_knocks, 0);
+
+    assert!(close(ada.gin_rate(), 0.25));
+    assert!(close(ada.knock_rate(), 0.0));
+    assert!(close(ada.undercut_given_rate(), 1.0 / 3.0));
+    assert!(close(ada.average_knock_deadwood(), 0.0));
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use std::slice;
+use std::time::Duration;
+
+use common::{start_server, Client};
+use gin::clock::{TimeControl, Timeout};
+use gin::protocol::{ClientMessage, ServerMessage, TableOptions};
+use gin::server::{Lobby, ServerOptions};
+use gin::{meld, Action, Phase};
+
+// starts a timed match between Alice and Bob, and returns who goes first and who second
+fn start_match(time_control: TimeControl) -> (Client, Client) {
+    let address = start_server(Lobby::create(ServerOptions {
+        time_control,
+        ..ServerOptions::standard()
+    }));
+    let mut alice = Client::connect(&address, "Alice");
+    alice.send(&ClientMessage::Create(TableOptions::standard()));
+    let table = alice.wait_for(|message| match message {
+        ServerMessage::Table(table) => Some(table),
+        _ => None,
+    });
+    let mut bob = Client::connect(&address, "Bob");
+    bob.send(&ClientMessage::Join(Some(table)));
+    let first = alice.wait_for(|message| match message {
+        ServerMessage::Info(text) if text.starts_with("new hand") => Some(text),
+        _ => None,
+    });
+    if first.contains("Alice goes first") {
+        (alice, bob)
+    } else {
+        (bob, alice)
+    }
+}
+
+#[test]
+fn a_stalled_turn_is_played_for_the_player() {
+    let (mut first, mut second) = start_match(TimeControl {
+        per_move: Some(Duration::from_millis(300)),
+        ..TimeControl::untimed()
+    });
+
+    // the first player waits out their turn, and sees the stock card drawn for them
+    let name = first.name.clone();
+    let drawn = first.wait_for(|message| match message {
+        ServerMessage::View(view) if view.turn == name && view.phase == Phase::Discard => {
+            Some(view)
+        }
+        _ => None,
+    });
+    let thrown = second.wait_for(|message| match message {
+        ServerMessage::Moved(player, Action::Discard(card)) if player == name => Some(card),
+        _ => None,
+    });
+    let (_, deadwood) = meld::best_melds(&drawn.hand);
+    let highest = deadwood
+        .iter()
+        .map(|card| meld::deadwood(slice::from_ref(card)))
+        .max();
+    assert_eq!(Some(meld::deadwood(&[thrown])), highest);
+
+    // and it is the second player's turn, with their own clock running
+    let view = second.view();
+    assert_eq!(view.turn, second.name);
+    assert_eq!(view.phase, Phase::Draw);
+}
+
+#[test]
+fn a_player_out_of_time_forfeits_the_hand() {
+    let (mut first, mut second) = start_match(TimeControl {
+        bank: Some(Duration::from_millis(300)),
+        increment: Duration::from_millis(100),
+        timeout: Timeout::Forfeit,
+        ..TimeControl::untimed()
+    });
+
+    let text = second.wait_for(|message| match message {
+        ServerMessage::Info(text) if text.contains("run out of time") => Some(text),
+        _ => None,
+    });
+    assert_eq!(
+        text,
+        format!("{} has run out of time and forfeits the hand", first.name)
+    );
+    let (winner, _) = first.wait_for(|message| match message {
+        ServerMessage::Result(winner, points) => Some((winner, points)),
+        _ => None,
+    });
+    assert_eq!(winner, second.name);
+}
@@ -0,0 +1,65 @@
+// the full screen interface is left out without the terminal feature
+#![cfg(feature = "terminal")]
+
+use gin::bot::BotMatch;
+use gin::record::HandRecord;
+use gin::tui::Tui;
+use gin::Phase;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::Terminal;
+
+// Grace's first turn of the recorded match
+fn first_turn() -> Tui {
+    let record = HandRecord::load("tests/hands/42-1.log").unwrap();
+    Tui::new(BotMatch::with_game("Grace", record.play_to(2).unwrap()))
+}
+
+fn screen(tui: &Tui) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|frame| tui.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[test]
+fn the_hand_is_laid_out_in_melds_then_deadwood() {
+    let text = screen(&first_turn());
+    assert!(text.contains("Grace - deadwood 17"));
+    // the sets first, then the rest by suit and rank
+    assert!(text.contains("│3♠ ││3♣ ││3♦ │  │6♦ ││6♥ ││6♠ │  │A♣ ││A♠ ││K♦ ││5♥ │"));
+    // the opponent's hand is never shown, only what they took from the discard pile
+    assert!(text.contains("10 cards in hand"));
+    assert!(text.contains("picked up from the discard pile: Q♣"));
+    assert!(!text.contains("J♦"));
+    assert!(text.contains("│K♠ │"));
+}
+
+#[test]
+fn single_keys_play_the_turn() {
+    let mut tui = first_turn();
+    tui.key(KeyCode::Char('k'));
+    assert!(screen(&tui).contains("you need to draw first"));
+
+    tui.key(KeyCode::Char('d'));
+    let view = tui.bot_match.game.view("Grace");
+    assert_eq!(view.phase, Phase::Discard);
+    assert_eq!(view.hand.len(), 11);
+
+    // the third card along is the three of diamonds
+    tui.key(KeyCode::Right);
+    tui.key(KeyCode::Right);
+    tui.key(KeyCode::Enter);
+    let view = tui.bot_match.game.view("Grace");
+    assert_eq!(view.turn, "Ada");
+    assert_eq!(view.hand.len(), 10);
+    assert!(screen(&tui).contains("Grace discards the 3♦"));
+    assert!(!tui.key(KeyCode::Char('q')));
+}
@@ -0,0 +1,65 @@
+use gin::cards::CardStyle;
+use gin::protocol::ServerMessage;
+use gin::record::HandRecord;
+use gin::{Card, Phase};
+
+fn code(card: &Card) -> String {
+    card.face(CardStyle {
+        symbols: false,
+        colour: false,
+    })
+}
+
+// every card code the player is sent, over both the line and the JSON protocol
+fn sent(view: &gin::view::PlayerView) -> String {
+    let message = ServerMessage::View(view.clone());
+    format!("{}\n{}", message.to_line(), message.to_json())
+}
+
+fn sent_card(text: &str, card: &Card) -> bool {
+    let code = code(card);
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| word == code)
+}
+
+#[test]
+fn a_view_hides_what_the_player_should_not_see() {
+    // Grace knocks with the 8h in the recorded hand
+    let record = HandRecord::load("tests/hands/42-1.log").unwrap();
+    let game = record.play_to(13).unwrap();
+    assert_eq!(game.view("Ada").phase, Phase::Showdown);
+    let ada = game.view("Ada");
+    let grace = game.view("Grace");
+
+    assert_eq!(ada.opponent_cards, grace.hand.len());
+    assert!(ada.opponent_deadwood.is_none());
+    assert!(ada.opponent_pickups.is_empty());
+    let text = sent(&ada);
+    for card in grace.hand.iter() {
+        assert!(!sent_card(&text, card), "Ada sees Grace's {}", code(card));
+    }
+
+    // the knocking discard is face down for Ada but not for Grace
+    assert_eq!(code(grace.discard_pile.last().unwrap()), "8h");
+    assert_eq!(ada.discard_pile.len() + 1, grace.discard_pile.len());
+    assert!(!sent_card(&text, grace.discard_pile.last().unwrap()));
+
+    // only the size of the stock is given away
+    let stock: Vec<&Card> = record
+        .deck
+        .iter()
+        .filter(|card| {
+            !ada.hand.contains(card)
+                && !grace.hand.contains(card)
+                && !grace.discard_pile.contains(card)
+        })
+        .collect();
+    assert_eq!(ada.stock, stock.len());
+    for card in stock {
+        assert!(
+            !sent_card(&text, card),
+            "Ada sees the {} in the stock",
+            code(card)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use gin::command::parse_card;
+use gin::record::HandRecord;
+use gin::web::WebGame;
+use gin::Action;
+use serde_json::Value;
+
+fn json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn legal_actions_are_the_moves_the_rules_allow() {
+    // Grace to draw in the recorded match, then to discard
+    let record = HandRecord::load("tests/hands/42-1.log").unwrap();
+    let game = record.play_to(2).unwrap();
+    assert_eq!(
+        game.legal_actions(),
+        vec![Action::DrawStock, Action::DrawDiscard]
+    );
+    let game = record.play_to(3).unwrap();
+    let actions = game.legal_actions();
+    assert!(actions
+        .iter()
+        .all(|action| matches!(action, Action::Discard(_))));
+    assert!(!actions.is_empty());
+    assert!(record.play_to(13).unwrap().legal_actions().is_empty());
+}
+
+#[test]
+fn the_card_just_taken_is_not_counted_towards_going_down() {
+    // Ada holds 2c to 9c, 3h and 4d and takes the Ks: only throwing the Ks back would leave
+    // 10 or less, and that is not allowed
+    let log = "match 7\nfirst_player Ada\nsecond_player Grace\nplayed 1700000600\nseed 1\n\
+               rules Standard\nfirst_turn Ada\ndeck Qs Js Ts 9s 8s 7s 6s 5s 4s 3s 2s As Kh Qh \
+               Th 8h 6h 4h 2h Td 9d 7d 5d 3d 2d Ad Kc Qc Jc Tc Ac Ks 6d 4d 8d 3h Jd 9c Qd 8c Kd \
+               7c Jh 6c 9h 5c 7h 4c 5h 3c Ah 2c\nmove Ada: draw-discard\n";
+    let record = HandRecord::from_log(log).unwrap();
+    let game = record.play_to(1).unwrap();
+    let actions = game.legal_actions();
+    assert!(!actions.contains(&Action::Knock));
+    assert!(!actions.contains(&Action::Discard(parse_card("Ks").unwrap())));
+    assert_eq!(actions.len(), 10);
+}
+
+#[test]
+fn a_hand_against_the_bot_is_played_through_json() {
+    assert!(WebGame::new("Bot", "Standard").is_err());
+    assert!(WebGame::new("Ada", "Fast").is_err());
+    let mut game = WebGame::new("Ada", "Quick").unwrap();
+    assert!(game.next_hand().is_err());
+
+    for _ in 0..200 {
+        let view = json(&game.view());
+        if !view["result"].is_null() {
+            break;
+        }
+        if let Some(action) = game.bot_action() {
+            assert!(json(&action)["action"].is_string());
+            continue;
+        }
+        assert_eq!(view["type"], "state");
+        assert_eq!(view["turn"], "Ada");
+        assert!(game
+            .apply_action(r#"{"action": "discard", "card": "Zz"}"#)
+            .is_err());
+        // the stock and the last card in hand, so Ada never goes down
+        let actions = json(&game.legal_actions());
+        let action = actions.as_array().unwrap().last().unwrap();
+        let action = if action["action"] == "draw-discard" {
+            &actions[0]
+        } else {
+            action
+        };
+        game.apply_action(&action.to_string()).unwrap();
+    }
+
+    let view = json(&game.view());
+    assert_eq!(view["phase"], "over");
+    assert_eq!(view["target"], 50);
+    assert_eq!(game.legal_actions(), "[]");
+    let points = view["result"]["points"].as_i64().unwrap();
+    let totals = view["totals"].as_array().unwrap();
+    if view["result"]["winner"] == "Bot" {
+        assert_eq!(totals[1], points);
+    } else {
+        assert_eq!(totals[0], points);
+    }
+    assert!(game.bot_action().is_none());
+    // a big enough hand ends the match on its own
+    if game.match_over() {
+        assert!(game.next_hand().is_err());
+    } else {
+        game.next_hand().unwrap();
+        assert!(json(&game.view())["result"].is_null());
+    }
+}
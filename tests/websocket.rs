@@ -0,0 +1,113 @@
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use gin::protocol::PROTOCOL_VERSION;
+use gin::server::{self, Lobby};
+use serde_json::{json, Value};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{connect, Message, WebSocket};
+
+type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn join(address: &str, name: &str, join: Value) -> Client {
+    let (mut client, _) = connect(format!("ws://{}", address)).unwrap();
+    if let MaybeTlsStream::Plain(stream) = client.get_ref() {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .unwrap();
+    }
+    send(
+        &mut client,
+        json!({"type": "hello", "version": PROTOCOL_VERSION, "features": ["rules:Standard"]}),
+    );
+    send(&mut client, json!({"type": "name", "name": name}));
+    send(&mut client, join);
+    client
+}
+
+fn send(client: &mut Client, message: Value) {
+    client.send(Message::text(message.to_string())).unwrap();
+}
+
+fn receive(client: &mut Client) -> Value {
+    loop {
+        if let Message::Text(text) = client.read().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+// draws from the stock, knocks whenever the server allows it and otherwise discards, until
+// the hand is over
+fn play_hand(client: &mut Client, name: &str) -> Value {
+    let mut tried_knock = false;
+    let mut discards: Vec<Value> = Vec::new();
+    loop {
+        let message = receive(client);
+        match message["type"].as_str().unwrap() {
+            "result" => return message,
+            "error" => {
+                let card = discards.pop().expect("ran out of cards to discard");
+                send(
+                    client,
+                    json!({"type": "play", "action": "discard", "card": card}),
+                );
+            }
+            "state" if message["turn"] == name => {
+                assert!(message.get("opponent_hand").is_none());
+                match message["phase"].as_str().unwrap() {
+                    "draw" => {
+                        tried_knock = false;
+                        send(client, json!({"type": "play", "action": "draw-stock"}));
+                    }
+                    "discard" => {
+                        discards = message["hand"].as_array().unwrap().clone();
+                        if tried_knock {
+                            let card = discards.pop().unwrap();
+                            send(
+                                client,
+                                json!({"type": "play", "action": "discard", "card": card}),
+                            );
+                        } else {
+                            tried_knock = true;
+                            send(client, json!({"type": "play", "action": "knock"}));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn two_websocket_clients_play_a_full_hand() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || server::serve_websockets(listener, Lobby::shared()));
+
+    let mut host = join(&address, "Alice", json!({"type": "create"}));
+    let table = loop {
+        let message = receive(&mut host);
+        if message["type"] == "table" {
+            break message["table"].clone();
+        }
+    };
+    let mut guest = join(&address, "Bob", json!({"type": "join", "table": table}));
+
+    let guest_result = thread::spawn(move || play_hand(&mut guest, "Bob"));
+    let host_result = play_hand(&mut host, "Alice");
+    let guest_result = guest_result.join().unwrap();
+
+    assert_eq!(host_result, guest_result);
+    match host_result["winner"].as_str() {
+        Some(winner) => {
+            assert!(winner == "Alice" || winner == "Bob");
+            assert!(host_result["points"].as_i64().unwrap() >= 0);
+        }
+        // the stock ran out
+        None => assert_eq!(host_result["points"], 0),
+    }
+}
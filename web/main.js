@@ -0,0 +1,156 @@
+// The page's side of a match against the bot. Everything is played by the engine in pkg/, built
+// from src/web.rs (see the README); this only draws the view it returns and sends it the moves
+// picked here, so the page works with no server and no network.
+
+import init, { WebGame } from "./pkg/gin.js";
+
+const SUITS = { c: "♣", s: "♠", d: "♦", h: "♥" };
+
+let game;
+// how long to wait before each of the bot's moves, set by the engine once it is loaded
+let botPause;
+
+// a card code such as "Qh" or "Tc" as it is shown, e.g. Q♥ or 10♣
+function face(code) {
+  const rank = code[0] === "T" ? "10" : code[0];
+  return rank + SUITS[code[1]];
+}
+
+function card(code, onclick) {
+  const element = document.createElement(onclick === undefined ? "span" : "button");
+  element.className = "dh".includes(code[1]) ? "card red" : "card";
+  element.textContent = face(code);
+  if (onclick !== undefined) {
+    element.disabled = onclick === null;
+    element.onclick = onclick;
+  }
+  return element;
+}
+
+function melds(id, groups) {
+  document.getElementById(id).replaceChildren(...groups.map((meld) => {
+    const element = document.createElement("span");
+    element.className = "meld";
+    element.append(...meld.map((code) => card(code)));
+    return element;
+  }));
+}
+
+function button(text, onclick) {
+  const element = document.createElement("button");
+  element.textContent = text;
+  element.onclick = onclick;
+  return element;
+}
+
+function describe(player, action) {
+  switch (action.action) {
+    case "draw-stock": return `${player} draws from the stock`;
+    case "draw-discard": return `${player} takes the discard`;
+    case "knock": return `${player} knocks`;
+    case "gin": return `${player} goes gin`;
+    default: return `${player} discards ${face(action.card)}`;
+  }
+}
+
+function play(action) {
+  try {
+    game.apply_action(JSON.stringify(action));
+  } catch (error) {
+    render(error);
+    return;
+  }
+  render(describe(JSON.parse(game.view()).player, action));
+  setTimeout(botTurn, botPause);
+}
+
+function botTurn() {
+  const action = game.bot_action();
+  if (action === undefined) {
+    return;
+  }
+  const view = JSON.parse(game.view());
+  render(describe(view.opponent, JSON.parse(action)));
+  setTimeout(botTurn, botPause);
+}
+
+function render(message) {
+  const view = JSON.parse(game.view());
+  const actions = JSON.parse(game.legal_actions());
+  const legal = (name) => actions.find((action) => action.action === name);
+
+  document.getElementById("score").textContent =
+    `${view.player} ${view.totals[0]}, ${view.opponent} ${view.totals[1]}, playing to ${view.target}`;
+  document.getElementById("opponent").textContent = view.opponent_deadwood === null
+    ? `${view.opponent} holds ${view.opponent_cards} cards`
+    : `${view.opponent}'s melds and deadwood`;
+  const opponent = [...view.opponent_melds];
+  if (view.opponent_deadwood !== null && view.opponent_deadwood.length > 0) {
+    opponent.push(view.opponent_deadwood);
+  }
+  melds("opponent-melds", opponent);
+
+  document.getElementById("piles").textContent =
+    `Stock: ${view.stock} cards. Discard pile:`;
+  const top = view.discard_pile.at(-1);
+  document.getElementById("discard").replaceChildren(...(top === undefined ? [] : [card(top)]));
+
+  melds("melds", view.melds);
+  document.getElementById("hand").replaceChildren(...view.hand.map((code) => {
+    const discard = actions.find((action) => action.card === code);
+    return card(code, discard === undefined ? null : () => play(discard));
+  }));
+
+  const buttons = [];
+  if (legal("draw-stock")) {
+    buttons.push(button("Draw from the stock", () => play(legal("draw-stock"))));
+  }
+  if (legal("draw-discard")) {
+    buttons.push(button("Take the discard", () => play(legal("draw-discard"))));
+  }
+  if (legal("knock")) {
+    buttons.push(button("Knock", () => play(legal("knock"))));
+  }
+  if (legal("gin")) {
+    buttons.push(button("Gin", () => play(legal("gin"))));
+  }
+  if (view.result !== null && !game.match_over()) {
+    buttons.push(button("Next hand", () => {
+      game.next_hand();
+      render("A new hand is dealt");
+      setTimeout(botTurn, botPause);
+    }));
+  }
+  document.getElementById("actions").replaceChildren(...buttons);
+
+  if (view.result !== null) {
+    const result = view.result.winner === null
+      ? "The stock has run out, the hand is void."
+      : `${view.result.winner} scores ${view.result.points} points.`;
+    message = game.match_over()
+      ? `${result} ${view.totals[0] > view.totals[1] ? view.player : view.opponent} wins the match.`
+      : result;
+  } else if (view.phase === "discard" && view.turn === view.player && message === undefined) {
+    message = "Click a card to discard it";
+  }
+  document.getElementById("message").textContent = message ?? "";
+}
+
+document.getElementById("start").onsubmit = async (event) => {
+  event.preventDefault();
+  await init();
+  botPause = WebGame.bot_pause();
+  try {
+    game = new WebGame(
+      document.getElementById("name").value.trim(),
+      document.getElementById("rules").value,
+    );
+  } catch (error) {
+    alert(error);
+    return;
+  }
+  document.getElementById("start").hidden = true;
+  document.getElementById("table").hidden = false;
+  render();
+  setTimeout(botTurn, botPause);
+};